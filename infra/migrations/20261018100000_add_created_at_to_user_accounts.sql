@@ -0,0 +1,3 @@
+ALTER TABLE user_accounts ADD COLUMN created_at timestamptz NOT NULL DEFAULT NOW();
+
+CREATE INDEX user_accounts_project_id_created_at_idx ON user_accounts (project_id, created_at DESC, id DESC);
@@ -0,0 +1,3 @@
+ALTER TABLE auth_events ADD COLUMN user_account_id uuid REFERENCES user_accounts (id) ON DELETE SET NULL;
+
+CREATE INDEX auth_events_user_account_id_occurred_at_idx ON auth_events (user_account_id, occurred_at DESC);
@@ -0,0 +1,17 @@
+CREATE TABLE project_roles (
+	id uuid PRIMARY KEY,
+	project_id uuid NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+	name text NOT NULL,
+	description text,
+	is_default boolean NOT NULL DEFAULT FALSE,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	UNIQUE (project_id, name)
+);
+
+CREATE TABLE project_role_permissions (
+	role_id uuid NOT NULL REFERENCES project_roles (id) ON DELETE CASCADE,
+	permission_id uuid NOT NULL REFERENCES permissions (id) ON DELETE CASCADE,
+	PRIMARY KEY (role_id, permission_id)
+);
+
+CREATE INDEX project_role_permissions_permission_id_idx ON project_role_permissions (permission_id);
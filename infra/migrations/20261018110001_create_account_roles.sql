@@ -0,0 +1,8 @@
+CREATE TABLE account_roles (
+	account_id uuid NOT NULL REFERENCES user_accounts (id) ON DELETE CASCADE,
+	role_id uuid NOT NULL REFERENCES project_roles (id) ON DELETE CASCADE,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	PRIMARY KEY (account_id, role_id)
+);
+
+CREATE INDEX account_roles_role_id_idx ON account_roles (role_id);
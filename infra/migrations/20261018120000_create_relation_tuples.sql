@@ -0,0 +1,33 @@
+-- Monotonic per-project counter bumped by every tuple write; consistency tokens encode it.
+ALTER TABLE projects ADD COLUMN authz_revision bigint NOT NULL DEFAULT 0;
+
+CREATE TABLE relation_namespaces (
+	project_id uuid NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+	name text NOT NULL,
+	config jsonb NOT NULL,
+	updated_at timestamptz NOT NULL DEFAULT NOW(),
+	PRIMARY KEY (project_id, name)
+);
+
+-- `object#relation@subject`; `subject_relation` is empty for a direct subject and set for a
+-- userset subject such as `team:a#member`. Deleted tuples are kept until `deleted_revision`
+-- falls out of use so that checks can be evaluated at a fixed revision.
+CREATE TABLE relation_tuples (
+	id uuid PRIMARY KEY,
+	project_id uuid NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+	namespace text NOT NULL,
+	object_id text NOT NULL,
+	relation text NOT NULL,
+	subject_namespace text NOT NULL,
+	subject_id text NOT NULL,
+	subject_relation text NOT NULL DEFAULT '',
+	created_revision bigint NOT NULL,
+	deleted_revision bigint,
+	created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX relation_tuples_live_idx ON relation_tuples (
+	project_id, namespace, object_id, relation, subject_namespace, subject_id, subject_relation
+) WHERE deleted_revision IS NULL;
+
+CREATE INDEX relation_tuples_subject_idx ON relation_tuples (project_id, subject_namespace, subject_id);
@@ -0,0 +1,34 @@
+CREATE TABLE application_secrets (
+	id uuid PRIMARY KEY,
+	application_id uuid NOT NULL REFERENCES applications (id) ON DELETE CASCADE,
+	secret_hash text NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	expires_at timestamptz,
+	last_used_at timestamptz
+);
+
+CREATE INDEX application_secrets_application_id_idx ON application_secrets (application_id);
+
+-- Existing secrets become the first secret of their application. Ids are UUID v7 built from
+-- the current time in milliseconds, like the ones the service generates.
+INSERT INTO application_secrets (id, application_id, secret_hash)
+SELECT
+	encode(
+		set_bit(
+			set_bit(
+				overlay(
+					uuid_send(gen_random_uuid())
+					PLACING substring(int8send(floor(extract(epoch FROM clock_timestamp()) * 1000)::bigint) FROM 3)
+					FROM 1 FOR 6
+				),
+				52, 1
+			),
+			53, 1
+		),
+		'hex'
+	)::uuid,
+	id,
+	client_secret_hash
+FROM applications;
+
+ALTER TABLE applications DROP COLUMN client_secret_hash;
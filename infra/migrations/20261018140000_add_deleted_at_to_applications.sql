@@ -0,0 +1,5 @@
+-- Soft delete: a deleted application keeps its row so auth_events and grants stay intact
+-- until it is restored or purged.
+ALTER TABLE applications ADD COLUMN deleted_at timestamptz;
+
+CREATE INDEX applications_live_project_id_idx ON applications (project_id) WHERE deleted_at IS NULL;
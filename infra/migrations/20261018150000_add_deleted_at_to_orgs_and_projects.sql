@@ -0,0 +1,12 @@
+-- Soft delete for organizations and projects. Rows stay (with everything below them) until an
+-- owner purges them once the restore window has passed.
+ALTER TABLE organizations ADD COLUMN deleted_at timestamptz;
+ALTER TABLE projects ADD COLUMN deleted_at timestamptz;
+
+-- Names only need to be unique among live rows, so a deleted org or project does not hold on to
+-- its name. Restoring one whose name was taken in the meantime fails with a conflict.
+ALTER TABLE organizations DROP CONSTRAINT unique_organization_name;
+CREATE UNIQUE INDEX unique_organization_name ON organizations (name) WHERE deleted_at IS NULL;
+
+ALTER TABLE projects DROP CONSTRAINT unique_org_project_name;
+CREATE UNIQUE INDEX unique_org_project_name ON projects (org_id, name) WHERE deleted_at IS NULL;
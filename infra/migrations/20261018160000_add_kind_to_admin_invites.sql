@@ -0,0 +1,9 @@
+-- An invite either adds a member or hands the owner role from the issuer to an existing member.
+ALTER TABLE admin_invites
+	ADD COLUMN kind text NOT NULL DEFAULT 'membership' CHECK (kind IN ('membership', 'ownership_transfer'));
+
+-- At most one transfer can be pending per org or project.
+CREATE UNIQUE INDEX admin_invites_pending_org_transfer_idx ON admin_invites (org_id)
+	WHERE kind = 'ownership_transfer' AND status = 'pending';
+CREATE UNIQUE INDEX admin_invites_pending_project_transfer_idx ON admin_invites (project_id)
+	WHERE kind = 'ownership_transfer' AND status = 'pending';
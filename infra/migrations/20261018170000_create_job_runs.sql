@@ -0,0 +1,12 @@
+-- One row per background job execution, written by whichever replica held the job's lock.
+CREATE TABLE job_runs (
+	id uuid PRIMARY KEY,
+	job_name text NOT NULL,
+	status text NOT NULL CHECK (status IN ('succeeded', 'failed')),
+	started_at timestamptz NOT NULL,
+	finished_at timestamptz NOT NULL DEFAULT NOW(),
+	affected_rows bigint NOT NULL DEFAULT 0,
+	error text
+);
+
+CREATE INDEX job_runs_job_name_started_at_idx ON job_runs (job_name, started_at DESC);
@@ -0,0 +1,8 @@
+-- Email invites address someone without an admin account; the username is filled in when they
+-- register through the invite.
+ALTER TABLE admin_invites ALTER COLUMN invitee_username DROP NOT NULL;
+ALTER TABLE admin_invites ADD COLUMN invitee_email varchar(255);
+ALTER TABLE admin_invites
+	ADD CONSTRAINT admin_invites_invitee_check CHECK (invitee_username IS NOT NULL OR invitee_email IS NOT NULL);
+
+CREATE INDEX admin_invites_invitee_email_idx ON admin_invites (invitee_email);
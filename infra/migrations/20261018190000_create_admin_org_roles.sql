@@ -0,0 +1,20 @@
+CREATE TABLE admin_org_roles (
+	id uuid PRIMARY KEY,
+	org_id uuid NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+	name varchar(50) NOT NULL,
+	description text,
+	permissions text[] NOT NULL DEFAULT '{}',
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	UNIQUE (org_id, name)
+);
+
+-- A custom role narrows an admin membership down to the role's permissions.
+ALTER TABLE admin_org_memberships
+	ADD COLUMN custom_role_id uuid REFERENCES admin_org_roles (id) ON DELETE RESTRICT,
+	ADD CONSTRAINT admin_org_memberships_custom_role_check CHECK (custom_role_id IS NULL OR role = 'admin');
+ALTER TABLE admin_project_memberships
+	ADD COLUMN custom_role_id uuid REFERENCES admin_org_roles (id) ON DELETE RESTRICT,
+	ADD CONSTRAINT admin_project_memberships_custom_role_check CHECK (custom_role_id IS NULL OR role = 'admin');
+
+CREATE INDEX admin_org_memberships_custom_role_id_idx ON admin_org_memberships (custom_role_id);
+CREATE INDEX admin_project_memberships_custom_role_id_idx ON admin_project_memberships (custom_role_id);
@@ -0,0 +1,23 @@
+CREATE TABLE admin_personal_access_tokens (
+	id uuid PRIMARY KEY,
+	admin_user_id uuid NOT NULL REFERENCES admin_users (id) ON DELETE CASCADE,
+	name varchar(100) NOT NULL,
+	token_hash text NOT NULL,
+	-- Catalog permission names; requests get the intersection with the admin's own.
+	permissions text[] NOT NULL DEFAULT '{}',
+	expires_at timestamptz NOT NULL,
+	last_used_at timestamptz,
+	revoked_at timestamptz,
+	created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX admin_personal_access_tokens_admin_user_id_idx ON admin_personal_access_tokens (admin_user_id);
+
+-- The orgs a token may act on; nothing else is reachable with it.
+CREATE TABLE admin_personal_access_token_orgs (
+	token_id uuid NOT NULL REFERENCES admin_personal_access_tokens (id) ON DELETE CASCADE,
+	org_id uuid NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+	PRIMARY KEY (token_id, org_id)
+);
+
+CREATE INDEX admin_personal_access_token_orgs_org_id_idx ON admin_personal_access_token_orgs (org_id);
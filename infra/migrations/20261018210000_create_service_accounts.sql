@@ -0,0 +1,38 @@
+-- Service accounts are admin users owned by an org. They hold memberships like any admin but
+-- have no password; they get access tokens with their credentials instead.
+ALTER TABLE admin_users
+	ADD COLUMN kind text NOT NULL DEFAULT 'human' CHECK (kind IN ('human', 'service')),
+	ADD COLUMN owner_org_id uuid REFERENCES organizations (id) ON DELETE CASCADE,
+	ALTER COLUMN password_hash DROP NOT NULL,
+	ADD CONSTRAINT admin_users_kind_credentials_check CHECK (
+		(kind = 'human' AND owner_org_id IS NULL AND password_hash IS NOT NULL)
+		OR (kind = 'service' AND owner_org_id IS NOT NULL AND password_hash IS NULL)
+	);
+
+CREATE INDEX admin_users_owner_org_id_idx ON admin_users (owner_org_id);
+
+-- A client secret (client credentials) or a public key for JWT assertions (RFC 7523).
+CREATE TABLE admin_service_account_credentials (
+	id uuid PRIMARY KEY,
+	admin_user_id uuid NOT NULL REFERENCES admin_users (id) ON DELETE CASCADE,
+	kind text NOT NULL CHECK (kind IN ('client_secret', 'public_key')),
+	secret_hash text,
+	algorithm text CHECK (algorithm IN ('RS256', 'ES256', 'EdDSA')),
+	public_key text,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	last_used_at timestamptz,
+	CHECK (
+		(kind = 'client_secret' AND secret_hash IS NOT NULL AND algorithm IS NULL AND public_key IS NULL)
+		OR (kind = 'public_key' AND secret_hash IS NULL AND algorithm IS NOT NULL AND public_key IS NOT NULL)
+	)
+);
+
+CREATE INDEX admin_service_account_credentials_admin_user_id_idx ON admin_service_account_credentials (admin_user_id);
+
+-- `jti`s of accepted assertions, kept until they expire so none can be replayed.
+CREATE TABLE admin_service_account_assertions (
+	admin_user_id uuid NOT NULL REFERENCES admin_users (id) ON DELETE CASCADE,
+	jti varchar(255) NOT NULL,
+	expires_at timestamptz NOT NULL,
+	PRIMARY KEY (admin_user_id, jti)
+);
@@ -0,0 +1,17 @@
+-- Projects can opt out of admins signing in as their end users.
+ALTER TABLE projects ADD COLUMN allow_impersonation boolean NOT NULL DEFAULT true;
+
+-- Every impersonation token an admin minted, shown to the end user in their session list.
+CREATE TABLE user_impersonations (
+	id uuid PRIMARY KEY,
+	user_account_id uuid NOT NULL REFERENCES user_accounts (id) ON DELETE CASCADE,
+	admin_user_id uuid REFERENCES admin_users (id) ON DELETE SET NULL,
+	-- Kept so the user still sees who it was after the admin account is gone.
+	admin_username varchar(50) NOT NULL,
+	application_id uuid REFERENCES applications (id) ON DELETE SET NULL,
+	reason varchar(500) NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	expires_at timestamptz NOT NULL
+);
+
+CREATE INDEX user_impersonations_user_account_id_idx ON user_impersonations (user_account_id, created_at DESC);
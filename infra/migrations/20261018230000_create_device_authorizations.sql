@@ -0,0 +1,20 @@
+-- Device authorization requests (RFC 8628): a device polls with its device code while the user
+-- enters the user code on the verification page and signs in.
+CREATE TABLE device_authorizations (
+	id uuid PRIMARY KEY,
+	application_id uuid NOT NULL REFERENCES applications (id) ON DELETE CASCADE,
+	device_code_hash text NOT NULL,
+	-- Typed by the user, e.g. `WDJB-MJHT`. Freed when the expired request is purged.
+	user_code varchar(9) NOT NULL UNIQUE,
+	-- Requested permission names; NULL asks for everything the account holds on the application.
+	scopes text[],
+	status text NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'denied', 'redeemed')),
+	user_account_id uuid REFERENCES user_accounts (id) ON DELETE CASCADE,
+	poll_interval_secs integer NOT NULL DEFAULT 5,
+	last_polled_at timestamptz,
+	expires_at timestamptz NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT NOW(),
+	CHECK (status NOT IN ('approved', 'redeemed') OR user_account_id IS NOT NULL)
+);
+
+CREATE INDEX device_authorizations_expires_at_idx ON device_authorizations (expires_at);
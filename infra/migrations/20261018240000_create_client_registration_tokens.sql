@@ -0,0 +1,29 @@
+-- Projects opt in to partners registering their own applications (RFC 7591).
+ALTER TABLE projects ADD COLUMN allow_dynamic_registration boolean NOT NULL DEFAULT false;
+
+-- Initial access tokens an admin hands to a partner; each registration counts against `max_registrations`.
+CREATE TABLE client_registration_tokens (
+	id uuid PRIMARY KEY,
+	project_id uuid NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+	name varchar(100) NOT NULL,
+	token_hash text NOT NULL,
+	created_by_admin_user_id uuid REFERENCES admin_users (id) ON DELETE SET NULL,
+	-- NULL allows any number of registrations until the token expires.
+	max_registrations integer CHECK (max_registrations > 0),
+	registrations integer NOT NULL DEFAULT 0,
+	expires_at timestamptz NOT NULL,
+	revoked_at timestamptz,
+	created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX client_registration_tokens_project_id_idx ON client_registration_tokens (project_id);
+
+-- Client metadata. Applications created by admins keep the defaults, which allow every grant.
+ALTER TABLE applications
+	ADD COLUMN grant_types text[] NOT NULL DEFAULT '{password,urn:ietf:params:oauth:grant-type:token-exchange,urn:ietf:params:oauth:grant-type:device_code}',
+	ADD COLUMN token_endpoint_auth_method text NOT NULL DEFAULT 'client_secret_basic'
+		CHECK (token_endpoint_auth_method IN ('client_secret_basic', 'none')),
+	-- Set for dynamically registered clients only; authorizes the RFC 7592 management endpoint.
+	ADD COLUMN registration_access_token_hash text,
+	ADD COLUMN registration_token_id uuid REFERENCES client_registration_tokens (id) ON DELETE SET NULL,
+	ADD COLUMN created_at timestamptz NOT NULL DEFAULT NOW();
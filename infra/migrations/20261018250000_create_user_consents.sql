@@ -0,0 +1,17 @@
+-- Third-party applications only get tokens for scopes the user approved. Applications created by
+-- admins stay first-party; dynamically registered ones never are.
+ALTER TABLE applications ADD COLUMN first_party boolean NOT NULL DEFAULT true;
+
+-- One row per set of scopes a user approved for an application together.
+CREATE TABLE user_consents (
+	id uuid PRIMARY KEY,
+	user_account_id uuid NOT NULL REFERENCES user_accounts (id) ON DELETE CASCADE,
+	application_id uuid NOT NULL REFERENCES applications (id) ON DELETE CASCADE,
+	-- Sorted and deduplicated, so the same set is recognized however it was requested.
+	scopes text[] NOT NULL,
+	granted_at timestamptz NOT NULL DEFAULT NOW(),
+	revoked_at timestamptz
+);
+
+CREATE UNIQUE INDEX user_consents_active_idx ON user_consents (user_account_id, application_id, scopes)
+	WHERE revoked_at IS NULL;
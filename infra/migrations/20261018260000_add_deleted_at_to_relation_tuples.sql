@@ -0,0 +1,8 @@
+-- When a tuple was deleted. Snapshots are opened at the latest revision and only live for one
+-- request, so once a deletion is older than any request still in flight no read can see the
+-- tuple any more and the `purge_relation_tuples` job removes it.
+ALTER TABLE relation_tuples ADD COLUMN deleted_at timestamptz;
+
+UPDATE relation_tuples SET deleted_at = NOW() WHERE deleted_revision IS NOT NULL;
+
+CREATE INDEX relation_tuples_deleted_at_idx ON relation_tuples (deleted_at) WHERE deleted_at IS NOT NULL;
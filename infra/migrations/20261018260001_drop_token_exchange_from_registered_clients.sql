@@ -0,0 +1,4 @@
+-- Dynamically registered clients may no longer use token exchange; take it from those that asked for it.
+UPDATE applications
+SET grant_types = array_remove(grant_types, 'urn:ietf:params:oauth:grant-type:token-exchange')
+WHERE registration_access_token_hash IS NOT NULL;
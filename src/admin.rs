@@ -1,25 +1,46 @@
 use crate::error::AppError;
 use crate::id;
 use crate::jwt;
-use axum::extract::Request;
+use crate::router::AppState;
+use axum::extract::{Request, State};
 use axum::middleware::Next;
 use axum::response::Response;
 use tracing::info;
 
 pub mod authorization;
+pub mod policy;
+pub mod retention;
 pub mod router;
+pub mod tokens;
 
-pub async fn validate_admin_api_key_middleware(mut request: Request, next: Next) -> Result<Response, AppError> {
+/// Accepts an admin access JWT or a personal access token. For the latter, the token's scope goes
+/// into the request extensions next to the admin ID; a service account's JWT is marked there too.
+pub async fn validate_admin_api_key_middleware(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
     info!("Validating admin API key");
     let header = request.headers();
     let jwt_token = jwt::get_jwt_token(header)?;
-    let claims = jwt::decode_admin_token(jwt_token)?.claims;
 
-    if claims.user_type != "admin" {
-        return Err(AppError::InvalidToken);
+    if jwt_token.starts_with(tokens::PREFIX) {
+        let (admin_id, scope) = tokens::authenticate(&state.pool, jwt_token).await?;
+        request.extensions_mut().insert(admin_id);
+        request.extensions_mut().insert(scope);
+        return Ok(next.run(request).await);
     }
 
+    let claims = jwt::decode_admin_token(jwt_token)?.claims;
+
     let admin_id = id::parse_uuid(&claims.sub).map_err(|_| AppError::InvalidToken)?;
+    match claims.user_type.as_str() {
+        "admin" => {}
+        "service" => {
+            request.extensions_mut().insert(authorization::ServiceAccountToken);
+        }
+        _ => return Err(AppError::InvalidToken),
+    }
     request.extensions_mut().insert(admin_id);
 
     Ok(next.run(request).await)
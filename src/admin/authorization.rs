@@ -1,8 +1,13 @@
+use super::policy::{self, Permission, PermissionMarker};
+use super::tokens::TokenScope;
 use crate::error::AppError;
 use crate::router::AppState;
 use axum::extract::{FromRequestParts, Path};
 use axum::http::request::Parts;
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -12,7 +17,7 @@ pub enum Role {
 }
 
 impl Role {
-    pub fn from_str(s: &str) -> Option<Self> {
+    pub fn parse(s: &str) -> Option<Self> {
         match s {
             "owner" => Some(Role::Owner),
             "admin" => Some(Role::Admin),
@@ -34,20 +39,85 @@ pub struct AdminId {
     pub admin_id: Uuid,
 }
 
-/// Extractor that verifies the caller is a member of the org in the URL path.
+/// Like `AdminId`, but refuses personal access tokens and service account tokens: for endpoints
+/// that manage the admin's own credentials, which a token must not be able to extend, and for
+/// those acting outside any one org, such as creating orgs or answering invites, which a token's
+/// org scope cannot bound.
+pub struct SessionAdmin {
+    pub admin_id: Uuid,
+}
+
+/// Put in the request extensions by the middleware when the caller is a service account.
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceAccountToken;
+
+/// Extractor that verifies the caller may act on the org in the URL path: as an org member, or
+/// as a member of some of its projects, who may only view the org and read their projects'
+/// metrics and logs.
 pub struct OrgMember {
     pub admin_id: Uuid,
     pub org_id: Uuid,
-    pub role: Role,
+    /// `None` when access comes from project memberships only.
+    pub role: Option<Role>,
+    /// What the caller may do here, per `policy::org_permissions`.
+    pub permissions: Vec<Permission>,
+    /// The projects the caller can see, when access comes from project memberships only;
+    /// `None` means every project of the org.
+    pub projects: Option<Vec<Uuid>>,
 }
 
-/// Extractor that verifies the caller has access to the project in the URL path.
-/// Checks direct project membership first; falls back to org membership for org owners.
+/// Like `OrgMember`, but also matches a soft-deleted org. Only restore and purge use it;
+/// everything else goes through `OrgMember`, which hides deleted orgs.
+pub struct AnyOrgMember {
+    pub admin_id: Uuid,
+    pub org_id: Uuid,
+    pub role: Option<Role>,
+    pub permissions: Vec<Permission>,
+    pub projects: Option<Vec<Uuid>>,
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+/// Extractor that verifies the caller has access to the project in the URL path, through a
+/// direct project membership, an org membership, or both.
 pub struct ProjectMember {
+    pub admin_id: Uuid,
+    pub project_id: Uuid,
+    pub org_id: Uuid,
+    /// The higher of the caller's project and org roles.
+    pub role: Role,
+    /// What the caller may do here, per `policy::project_permissions`.
+    pub permissions: Vec<Permission>,
+}
+
+/// Like `ProjectMember`, but also matches a soft-deleted project of a live org.
+pub struct AnyProjectMember {
     pub admin_id: Uuid,
     pub project_id: Uuid,
     pub org_id: Uuid,
     pub role: Role,
+    pub permissions: Vec<Permission>,
+    pub deleted_at: Option<OffsetDateTime>,
+}
+
+/// A membership extractor `RequirePermission` can check.
+pub trait Membership: FromRequestParts<AppState, Rejection = AppError> {
+    fn permissions(&self) -> &[Permission];
+}
+
+/// Extractor that resolves the membership `M` and requires it to grant `P`, e.g.
+/// `RequirePermission<perm::ProjectCreate>` or `RequirePermission<perm::AppCreate, ProjectMember>`.
+/// Derefs to the membership.
+pub struct RequirePermission<P, M = OrgMember> {
+    pub member: M,
+    _permission: PhantomData<P>,
+}
+
+impl<P, M> Deref for RequirePermission<P, M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.member
+    }
 }
 
 fn admin_id_from_parts(parts: &Parts) -> Result<Uuid, AppError> {
@@ -58,6 +128,14 @@ fn admin_id_from_parts(parts: &Parts) -> Result<Uuid, AppError> {
         .ok_or(AppError::InvalidToken)
 }
 
+/// Narrows `permissions` to the caller's personal access token, if the request was made with one.
+fn token_permissions(parts: &Parts, org_id: Uuid, permissions: Vec<Permission>) -> Result<Vec<Permission>, AppError> {
+    match parts.extensions.get::<TokenScope>() {
+        Some(scope) => scope.narrow(org_id, permissions),
+        None => Ok(permissions),
+    }
+}
+
 async fn path_params(parts: &mut Parts, state: &AppState) -> Result<HashMap<String, String>, AppError> {
     Path::<HashMap<String, String>>::from_request_parts(parts, state)
         .await
@@ -75,7 +153,21 @@ impl FromRequestParts<AppState> for AdminId {
     }
 }
 
-impl FromRequestParts<AppState> for OrgMember {
+impl FromRequestParts<AppState> for SessionAdmin {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &AppState) -> Result<Self, Self::Rejection> {
+        if parts.extensions.get::<TokenScope>().is_some() || parts.extensions.get::<ServiceAccountToken>().is_some() {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(SessionAdmin {
+            admin_id: admin_id_from_parts(parts)?,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for AnyOrgMember {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
@@ -85,25 +177,96 @@ impl FromRequestParts<AppState> for OrgMember {
         let org_id_str = params.get("org_id").ok_or(AppError::InvalidToken)?;
         let org_id = crate::id::parse_uuid(org_id_str)?;
 
-        let membership = sqlx::query!(
-            "SELECT role FROM admin_org_memberships WHERE admin_user_id = $1 AND org_id = $2",
+        let org = sqlx::query!(
+            r#"
+            SELECT o.deleted_at, m.role AS "role?", r.permissions AS "custom_permissions?"
+            FROM organizations o
+            LEFT JOIN admin_org_memberships m ON m.org_id = o.id AND m.admin_user_id = $1
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE o.id = $2
+            "#,
             admin_id,
             org_id
         )
         .fetch_optional(&state.pool)
         .await
+        .map_err(AppError::Sqlx)?
+        .ok_or(AppError::Forbidden)?;
+
+        if let Some(role) = org.role {
+            let role = Role::parse(&role).ok_or(AppError::InvalidToken)?;
+            return Ok(AnyOrgMember {
+                admin_id,
+                org_id,
+                role: Some(role),
+                permissions: token_permissions(
+                    parts,
+                    org_id,
+                    policy::org_permissions(Some(policy::granted(role, org.custom_permissions.as_deref())), &[]),
+                )?,
+                projects: None,
+                deleted_at: org.deleted_at,
+            });
+        }
+
+        // Members of some of the org's live projects may still view it.
+        let project_ms = sqlx::query!(
+            r#"
+            SELECT m.project_id, m.role, r.permissions AS "custom_permissions?"
+            FROM admin_project_memberships m
+            JOIN projects p ON p.id = m.project_id
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.admin_user_id = $1 AND p.org_id = $2 AND p.deleted_at IS NULL
+            "#,
+            admin_id,
+            org_id
+        )
+        .fetch_all(&state.pool)
+        .await
         .map_err(AppError::Sqlx)?;
 
-        let Some(m) = membership else {
+        if project_ms.is_empty() {
             return Err(AppError::Forbidden);
-        };
+        }
+
+        let mut grants = Vec::with_capacity(project_ms.len());
+        for m in &project_ms {
+            let role = Role::parse(&m.role).ok_or(AppError::InvalidToken)?;
+            grants.push(policy::granted(role, m.custom_permissions.as_deref()));
+        }
 
-        let role = Role::from_str(&m.role).ok_or(AppError::InvalidToken)?;
-        Ok(OrgMember { admin_id, org_id, role })
+        Ok(AnyOrgMember {
+            admin_id,
+            org_id,
+            role: None,
+            permissions: token_permissions(parts, org_id, policy::org_permissions(None, &grants))?,
+            projects: Some(project_ms.iter().map(|m| m.project_id).collect()),
+            deleted_at: org.deleted_at,
+        })
     }
 }
 
-impl FromRequestParts<AppState> for ProjectMember {
+impl FromRequestParts<AppState> for OrgMember {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let member = AnyOrgMember::from_request_parts(parts, state).await?;
+
+        if member.deleted_at.is_some() {
+            return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+        }
+
+        Ok(OrgMember {
+            admin_id: member.admin_id,
+            org_id: member.org_id,
+            role: member.role,
+            permissions: member.permissions,
+            projects: member.projects,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for AnyProjectMember {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
@@ -116,24 +279,30 @@ impl FromRequestParts<AppState> for ProjectMember {
         let project_id_str = params.get("project_id").ok_or(AppError::InvalidToken)?;
         let project_id = crate::id::parse_uuid(project_id_str)?;
 
-        // Verify the project belongs to the specified org.
-        let in_org: bool = sqlx::query_scalar!(
-            "SELECT EXISTS(SELECT 1 FROM projects WHERE id = $1 AND org_id = $2)",
+        // Verify the project belongs to the specified org, and that the org is live.
+        let project = sqlx::query!(
+            r#"
+            SELECT p.deleted_at
+            FROM projects p
+            JOIN organizations o ON o.id = p.org_id
+            WHERE p.id = $1 AND p.org_id = $2 AND o.deleted_at IS NULL
+            "#,
             project_id,
             org_id
         )
-        .fetch_one(&state.pool)
+        .fetch_optional(&state.pool)
         .await
         .map_err(AppError::Sqlx)?
-        .unwrap_or(false);
-
-        if !in_org {
-            return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
-        }
+        .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+        let deleted_at = project.deleted_at;
 
-        // Check direct project membership first.
         let project_m = sqlx::query!(
-            "SELECT role FROM admin_project_memberships WHERE admin_user_id = $1 AND project_id = $2",
+            r#"
+            SELECT m.role, r.permissions AS "custom_permissions?"
+            FROM admin_project_memberships m
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.admin_user_id = $1 AND m.project_id = $2
+            "#,
             admin_id,
             project_id
         )
@@ -141,14 +310,14 @@ impl FromRequestParts<AppState> for ProjectMember {
         .await
         .map_err(AppError::Sqlx)?;
 
-        if let Some(m) = project_m {
-            let role = Role::from_str(&m.role).ok_or(AppError::InvalidToken)?;
-            return Ok(ProjectMember { admin_id, project_id, org_id, role });
-        }
-
-        // Fall back: only org owners inherit all project access.
+        // Org members reach every project of the org.
         let org_m = sqlx::query!(
-            "SELECT role FROM admin_org_memberships WHERE admin_user_id = $1 AND org_id = $2",
+            r#"
+            SELECT m.role, r.permissions AS "custom_permissions?"
+            FROM admin_org_memberships m
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.admin_user_id = $1 AND m.org_id = $2
+            "#,
             admin_id,
             org_id
         )
@@ -156,14 +325,97 @@ impl FromRequestParts<AppState> for ProjectMember {
         .await
         .map_err(AppError::Sqlx)?;
 
-        match org_m {
-            Some(m) if m.role == "owner" => Ok(ProjectMember {
-                admin_id,
-                project_id,
-                org_id,
-                role: Role::Owner,
-            }),
-            _ => Err(AppError::Forbidden),
+        let mut roles = Vec::new();
+        let mut grants = Vec::new();
+        for (role, custom_permissions) in [
+            project_m.map(|m| (m.role, m.custom_permissions)),
+            org_m.map(|m| (m.role, m.custom_permissions)),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let role = Role::parse(&role).ok_or(AppError::InvalidToken)?;
+            roles.push(role);
+            grants.push(policy::granted(role, custom_permissions.as_deref()));
+        }
+
+        if roles.is_empty() {
+            return Err(AppError::Forbidden);
         }
+        let role = if roles.contains(&Role::Owner) {
+            Role::Owner
+        } else {
+            Role::Admin
+        };
+
+        Ok(AnyProjectMember {
+            admin_id,
+            project_id,
+            org_id,
+            role,
+            permissions: token_permissions(parts, org_id, policy::project_permissions(&grants))?,
+            deleted_at,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for ProjectMember {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let member = AnyProjectMember::from_request_parts(parts, state).await?;
+
+        if member.deleted_at.is_some() {
+            return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+        }
+
+        Ok(ProjectMember {
+            admin_id: member.admin_id,
+            project_id: member.project_id,
+            org_id: member.org_id,
+            role: member.role,
+            permissions: member.permissions,
+        })
+    }
+}
+
+impl Membership for OrgMember {
+    fn permissions(&self) -> &[Permission] {
+        &self.permissions
+    }
+}
+
+impl Membership for AnyOrgMember {
+    fn permissions(&self) -> &[Permission] {
+        &self.permissions
+    }
+}
+
+impl Membership for ProjectMember {
+    fn permissions(&self) -> &[Permission] {
+        &self.permissions
+    }
+}
+
+impl Membership for AnyProjectMember {
+    fn permissions(&self) -> &[Permission] {
+        &self.permissions
+    }
+}
+
+impl<P: PermissionMarker, M: Membership> FromRequestParts<AppState> for RequirePermission<P, M> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let member = M::from_request_parts(parts, state).await?;
+
+        if !member.permissions().contains(&P::PERMISSION) {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(RequirePermission {
+            member,
+            _permission: PhantomData,
+        })
     }
 }
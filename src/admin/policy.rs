@@ -0,0 +1,149 @@
+use super::authorization::Role;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Marks a type as standing for one catalog permission, so handlers can name it in
+/// `RequirePermission<perm::...>`.
+pub trait PermissionMarker {
+    const PERMISSION: Permission;
+}
+
+macro_rules! permission_catalog {
+    ($($variant:ident => $name:literal, $owner_only:literal, $description:literal;)*) => {
+        /// Everything an admin can be allowed to do in an org or project. Built-in roles and
+        /// custom org roles are sets of these.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+        pub enum Permission {
+            $(
+                #[doc = $description]
+                #[serde(rename = $name)]
+                $variant,
+            )*
+        }
+
+        impl Permission {
+            pub const ALL: &[Permission] = &[$(Permission::$variant),*];
+
+            pub fn parse(s: &str) -> Option<Self> {
+                match s {
+                    $($name => Some(Permission::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Permission::$variant => $name,)*
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Permission::$variant => $description,)*
+                }
+            }
+
+            /// Reserved to owners: the `admin` role lacks it and custom roles cannot include it.
+            pub fn owner_only(self) -> bool {
+                match self {
+                    $(Permission::$variant => $owner_only,)*
+                }
+            }
+        }
+
+        /// Marker types for `RequirePermission`, one per catalog permission.
+        pub mod perm {
+            $(
+                pub struct $variant;
+
+                impl super::PermissionMarker for $variant {
+                    const PERMISSION: super::Permission = super::Permission::$variant;
+                }
+            )*
+        }
+    };
+}
+
+permission_catalog! {
+    OrgRead => "org.read", false, "View the organization, its members, roles and invites";
+    OrgUpdate => "org.update", false, "Rename the organization";
+    OrgDelete => "org.delete", false, "Delete and restore the organization";
+    OrgPurge => "org.purge", true, "Erase a deleted organization once the restore window has passed";
+    ProjectCreate => "project.create", false, "Create projects";
+    ProjectRead => "project.read", false, "View projects and their applications, settings, end-user roles and namespaces";
+    ProjectUpdate => "project.update", false, "Edit projects and their relation namespaces";
+    ProjectDelete => "project.delete", false, "Delete and restore projects";
+    ProjectPurge => "project.purge", true, "Erase deleted projects once the restore window has passed";
+    AppCreate => "app.create", false, "Create applications";
+    AppUpdate => "app.update", false, "Edit applications";
+    AppDelete => "app.delete", false, "Delete and restore applications";
+    AppPurge => "app.purge", true, "Erase deleted applications once the restore window has passed";
+    AppScopesManage => "app.scopes.manage", false, "Manage application permissions and scopes, and the end-user roles that bundle them";
+    AppSecretRotate => "app.secret.rotate", false, "Create and revoke application client secrets";
+    MembersInvite => "members.invite", false, "Invite admins and list or revoke invites";
+    MembersRemove => "members.remove", false, "Remove non-owner members";
+    MembersRoleUpdate => "members.role.update", true, "Change the role of members";
+    OwnershipTransfer => "ownership.transfer", true, "Hand the owner role over to another member";
+    RolesManage => "roles.manage", true, "Create, edit and delete custom roles";
+    ServiceAccountsManage => "service_accounts.manage", false, "Create and delete service accounts and their credentials";
+    UsersRead => "users.read", false, "View end users and their events";
+    UsersManage => "users.manage", false, "Delete and verify end users, reset their passwords and grant them scopes and roles";
+    UsersImpersonate => "users.impersonate", false, "Act as an end user with a short-lived token, where the project allows it";
+    LogsRead => "logs.read", false, "Read auth event logs";
+    MetricsRead => "metrics.read", false, "Read metrics";
+}
+
+/// Whether a built-in role grants `permission`: owners hold the whole catalog, admins all but
+/// the owner-only permissions.
+pub fn role_grants(role: Role, permission: Permission) -> bool {
+    match role {
+        Role::Owner => true,
+        Role::Admin => !permission.owner_only(),
+    }
+}
+
+/// Permissions a membership grants: its custom role's set when it has one, its built-in role's
+/// otherwise. Unknown or owner-only names stored on a custom role are ignored.
+pub fn granted(role: Role, custom_role_permissions: Option<&[String]>) -> Vec<Permission> {
+    match custom_role_permissions {
+        Some(names) => names
+            .iter()
+            .filter_map(|name| Permission::parse(name))
+            .filter(|permission| !permission.owner_only())
+            .collect(),
+        None => Permission::ALL
+            .iter()
+            .copied()
+            .filter(|permission| role_grants(role, *permission))
+            .collect(),
+    }
+}
+
+/// Permissions on an org path. Org members get their membership's; callers who are only members
+/// of some of the org's projects may view the org and, where a project membership allows it,
+/// read metrics and logs, which handlers narrow to those projects (`OrgMember::projects`).
+pub fn org_permissions(org: Option<Vec<Permission>>, projects: &[Vec<Permission>]) -> Vec<Permission> {
+    match org {
+        Some(permissions) => permissions,
+        None if projects.is_empty() => Vec::new(),
+        None => Permission::ALL
+            .iter()
+            .copied()
+            .filter(|permission| match permission {
+                Permission::OrgRead => true,
+                Permission::MetricsRead | Permission::LogsRead => projects.iter().any(|p| p.contains(permission)),
+                _ => false,
+            })
+            .collect(),
+    }
+}
+
+/// Permissions on a project path: the union of the caller's project and org memberships, since
+/// org members reach every project of their org.
+pub fn project_permissions(memberships: &[Vec<Permission>]) -> Vec<Permission> {
+    Permission::ALL
+        .iter()
+        .copied()
+        .filter(|permission| memberships.iter().any(|m| m.contains(permission)))
+        .collect()
+}
@@ -0,0 +1,46 @@
+use crate::error::{AppError, ValidationErrors};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+
+/// How long a soft-deleted resource can be restored. Once it has passed, the resource is
+/// only kept for its history and may be purged.
+pub const RESTORE_WINDOW: Duration = Duration::days(30);
+
+pub fn within_restore_window(deleted_at: OffsetDateTime) -> bool {
+    OffsetDateTime::now_utc() - deleted_at <= RESTORE_WINDOW
+}
+
+/// Fails with a validation error on `field` once the restore window has passed.
+pub fn ensure_restorable(field: &str, deleted_at: OffsetDateTime) -> Result<(), AppError> {
+    if within_restore_window(deleted_at) {
+        return Ok(());
+    }
+
+    let mut errors = HashMap::new();
+    errors.insert(
+        field.to_string(),
+        vec![format!(
+            "can only be restored within {} days of deletion",
+            RESTORE_WINDOW.whole_days()
+        )],
+    );
+    Err(AppError::ValidationError(ValidationErrors::new(errors)))
+}
+
+/// Fails with a validation error on `field` while the resource can still be restored, so a
+/// purge never races a restore.
+pub fn ensure_purgeable(field: &str, deleted_at: OffsetDateTime) -> Result<(), AppError> {
+    if !within_restore_window(deleted_at) {
+        return Ok(());
+    }
+
+    let mut errors = HashMap::new();
+    errors.insert(
+        field.to_string(),
+        vec![format!(
+            "can only be purged {} days after deletion",
+            RESTORE_WINDOW.whole_days()
+        )],
+    );
+    Err(AppError::ValidationError(ValidationErrors::new(errors)))
+}
@@ -1,10 +1,10 @@
 use crate::admin;
-use crate::admin::authorization::{AdminId, OrgMember, ProjectMember};
-use crate::crypto;
+use crate::admin::authorization::{AdminId, ProjectMember, RequirePermission, SessionAdmin};
+use crate::admin::policy::perm;
 use crate::error::{AppError, ValidationErrors};
+use crate::id;
 use crate::pagination::{self, CursorPage, CursorParams};
 use crate::router::AppState;
-use crate::id;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
@@ -16,39 +16,160 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 use {time, uuid};
 
+mod account_scopes;
+mod admin_roles;
+pub(crate) mod application_secrets;
+mod applications;
 mod auth;
 mod invites;
-
-pub fn get_router() -> OpenApiRouter<AppState> {
+mod members;
+mod orgs;
+mod ownership;
+mod permissions;
+mod personal_access_tokens;
+mod projects;
+mod registration_tokens;
+mod relation_namespaces;
+mod roles;
+mod service_accounts;
+mod users;
+
+pub fn get_router(state: AppState) -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         // Identity
         .routes(routes!(me_handler))
         .routes(routes!(list_admin_users_handler))
+        .routes(routes!(admin_roles::list_permission_catalog_handler))
+        .routes(routes!(
+            personal_access_tokens::create_token_handler,
+            personal_access_tokens::list_tokens_handler
+        ))
+        .routes(routes!(personal_access_tokens::revoke_token_handler))
         // Orgs
         .routes(routes!(create_org_handler))
         .routes(routes!(list_orgs_handler))
-        .routes(routes!(get_org_handler))
+        .routes(routes!(
+            get_org_handler,
+            orgs::update_org_handler,
+            orgs::delete_org_handler
+        ))
+        .routes(routes!(orgs::restore_org_handler))
+        .routes(routes!(orgs::purge_org_handler))
+        .routes(routes!(members::list_org_members_handler))
+        .routes(routes!(
+            members::update_org_member_handler,
+            members::remove_org_member_handler
+        ))
+        .routes(routes!(members::leave_org_handler))
+        .routes(routes!(ownership::propose_org_transfer_handler))
+        .routes(routes!(
+            admin_roles::create_admin_role_handler,
+            admin_roles::list_admin_roles_handler
+        ))
+        .routes(routes!(
+            admin_roles::update_admin_role_handler,
+            admin_roles::delete_admin_role_handler
+        ))
+        .routes(routes!(
+            service_accounts::create_service_account_handler,
+            service_accounts::list_service_accounts_handler
+        ))
+        .routes(routes!(service_accounts::delete_service_account_handler))
+        .routes(routes!(service_accounts::create_credential_handler))
+        .routes(routes!(service_accounts::delete_credential_handler))
         // Projects
         .routes(routes!(create_project_handler))
         .routes(routes!(list_projects_handler))
-        .routes(routes!(get_project_handler))
+        .routes(routes!(
+            get_project_handler,
+            projects::update_project_handler,
+            projects::delete_project_handler
+        ))
+        .routes(routes!(projects::restore_project_handler))
+        .routes(routes!(projects::purge_project_handler))
+        .routes(routes!(members::list_project_members_handler))
+        .routes(routes!(
+            members::update_project_member_handler,
+            members::remove_project_member_handler
+        ))
+        .routes(routes!(members::leave_project_handler))
+        .routes(routes!(ownership::propose_project_transfer_handler))
         // Applications
         .routes(routes!(create_application_handler))
         .routes(routes!(list_applications_handler))
-        .routes(routes!(applications_scopes_handler))
+        .routes(routes!(
+            applications::update_application_handler,
+            applications::delete_application_handler
+        ))
+        .routes(routes!(applications::restore_application_handler))
+        .routes(routes!(applications::purge_application_handler))
+        .routes(routes!(
+            applications_scopes_handler,
+            permissions::list_permissions_handler
+        ))
+        .routes(routes!(
+            permissions::update_permission_handler,
+            permissions::delete_permission_handler
+        ))
+        .routes(routes!(
+            application_secrets::create_secret_handler,
+            application_secrets::list_secrets_handler
+        ))
+        .routes(routes!(application_secrets::delete_secret_handler))
+        .routes(routes!(
+            registration_tokens::create_registration_token_handler,
+            registration_tokens::list_registration_tokens_handler
+        ))
+        .routes(routes!(registration_tokens::revoke_registration_token_handler))
+        // End users
+        .routes(routes!(users::list_users_handler))
+        .routes(routes!(users::get_user_handler, users::delete_user_handler))
+        .routes(routes!(users::verify_login_method_handler))
+        .routes(routes!(users::reset_user_password_handler))
+        .routes(routes!(users::impersonate_user_handler))
+        .routes(routes!(users::user_events_handler))
+        .routes(routes!(account_scopes::list_account_scopes_handler))
+        .routes(routes!(account_scopes::grant_account_scopes_handler))
+        .routes(routes!(account_scopes::revoke_account_scopes_handler))
+        .routes(routes!(roles::assign_role_handler, roles::unassign_role_handler))
+        // End-user roles
+        .routes(routes!(roles::create_role_handler, roles::list_roles_handler))
+        .routes(routes!(
+            roles::get_role_handler,
+            roles::update_role_handler,
+            roles::delete_role_handler
+        ))
+        // Relationship-based authorization
+        .routes(routes!(relation_namespaces::list_namespaces_handler))
+        .routes(routes!(
+            relation_namespaces::get_namespace_handler,
+            relation_namespaces::put_namespace_handler
+        ))
         // Monitoring
         .routes(routes!(metrics_handler))
         .routes(routes!(logs_handler))
         // Invites
-        .routes(routes!(invites::create_org_invite_handler))
-        .routes(routes!(invites::create_project_invite_handler))
+        .routes(routes!(invites::list_incoming_invites_handler))
+        .routes(routes!(
+            invites::create_org_invite_handler,
+            invites::list_org_invites_handler
+        ))
+        .routes(routes!(
+            invites::create_project_invite_handler,
+            invites::list_project_invites_handler
+        ))
         .routes(routes!(invites::accept_invite_handler))
         .routes(routes!(invites::decline_invite_handler))
         .routes(routes!(invites::revoke_invite_handler))
-        .layer(middleware::from_fn(admin::validate_admin_api_key_middleware))
+        .layer(middleware::from_fn_with_state(
+            state,
+            admin::validate_admin_api_key_middleware,
+        ))
         // Public routes — no JWT required
         .routes(routes!(auth::register_admin_handler))
         .routes(routes!(auth::login_admin_handler))
+        .routes(routes!(service_accounts::service_account_token_handler))
+        .routes(routes!(invites::register_with_invite_handler))
 }
 
 // ─── Response / request structs ──────────────────────────────────────────────
@@ -57,6 +178,8 @@ pub fn get_router() -> OpenApiRouter<AppState> {
 pub struct AdminUserResponse {
     id: String,
     username: String,
+    /// `human`, or `service` for a service account owned by an org.
+    kind: String,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -90,6 +213,10 @@ pub struct ProjectResponse {
     org_id: String,
     name: String,
     shared_identity_context: bool,
+    /// Whether admins may act as the project's end users.
+    allow_impersonation: bool,
+    /// Whether partners holding an initial access token may register applications themselves.
+    allow_dynamic_registration: bool,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -109,6 +236,7 @@ pub struct ApplicationListItem {
     id: String,
     name: String,
     client_id: String,
+    first_party: bool,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -171,18 +299,16 @@ async fn me_handler(
     AdminId { admin_id }: AdminId,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
-    let record = sqlx::query!(
-        "SELECT id, username FROM admin_users WHERE id = $1",
-        admin_id
-    )
-    .fetch_one(&state.pool)
-    .await?;
+    let record = sqlx::query!("SELECT id, username, kind FROM admin_users WHERE id = $1", admin_id)
+        .fetch_one(&state.pool)
+        .await?;
 
     Ok((
         StatusCode::OK,
         Json(AdminUserResponse {
             id: record.id.to_string(),
             username: record.username,
+            kind: record.kind,
         }),
     ))
 }
@@ -203,7 +329,7 @@ async fn list_admin_users_handler(
 ) -> Result<impl IntoResponse, AppError> {
     let records = sqlx::query!(
         r#"
-            SELECT DISTINCT au.id, au.username
+            SELECT DISTINCT au.id, au.username, au.kind
             FROM admin_users au
             JOIN admin_org_memberships m2 ON au.id = m2.admin_user_id
             JOIN admin_org_memberships m1 ON m2.org_id = m1.org_id
@@ -220,6 +346,7 @@ async fn list_admin_users_handler(
         .map(|r| AdminUserResponse {
             id: r.id.to_string(),
             username: r.username,
+            kind: r.kind,
         })
         .collect();
 
@@ -237,10 +364,11 @@ async fn list_admin_users_handler(
     responses(
         (status = 201, description = "Organization created; returns its UUID"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Personal access tokens cannot create organizations"),
     )
 )]
 async fn create_org_handler(
-    AdminId { admin_id }: AdminId,
+    SessionAdmin { admin_id }: SessionAdmin,
     State(state): State<AppState>,
     Json(body): Json<CreateOrgRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -279,10 +407,11 @@ async fn create_org_handler(
     responses(
         (status = 200, description = "Organizations the admin belongs to", body = Vec<OrgListItem>),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Personal access tokens cannot list the admin's organizations"),
     )
 )]
 async fn list_orgs_handler(
-    AdminId { admin_id }: AdminId,
+    SessionAdmin { admin_id }: SessionAdmin,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let records = sqlx::query!(
@@ -290,7 +419,7 @@ async fn list_orgs_handler(
             SELECT o.id, o.name, m.role
             FROM organizations o
             JOIN admin_org_memberships m ON o.id = m.org_id
-            WHERE m.admin_user_id = $1
+            WHERE m.admin_user_id = $1 AND o.deleted_at IS NULL
             ORDER BY o.name
         "#,
         admin_id
@@ -324,7 +453,7 @@ async fn list_orgs_handler(
     )
 )]
 async fn get_org_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::OrgRead>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let record = sqlx::query!(
@@ -359,7 +488,7 @@ async fn get_org_handler(
     )
 )]
 async fn create_project_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::ProjectCreate>,
     State(state): State<AppState>,
     Json(body): Json<CreateProjectRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -405,18 +534,24 @@ async fn create_project_handler(
     security(("bearer_auth" = [])),
     params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
     responses(
-        (status = 200, description = "Projects in the organization", body = Vec<ProjectListItem>),
+        (status = 200, description = "Projects in the organization the caller can see", body = Vec<ProjectListItem>),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
     )
 )]
 async fn list_projects_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::OrgRead>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let records = sqlx::query!(
-        "SELECT id, name, shared_identity_context FROM projects WHERE org_id = $1 ORDER BY name",
-        member.org_id
+        r#"
+            SELECT id, name, shared_identity_context
+            FROM projects
+            WHERE org_id = $1 AND deleted_at IS NULL AND ($2::uuid[] IS NULL OR id = ANY($2))
+            ORDER BY name
+        "#,
+        member.org_id,
+        member.projects.as_deref(),
     )
     .fetch_all(&state.pool)
     .await?;
@@ -450,11 +585,11 @@ async fn list_projects_handler(
     )
 )]
 async fn get_project_handler(
-    member: ProjectMember,
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let record = sqlx::query!(
-        "SELECT id, org_id, name, shared_identity_context FROM projects WHERE id = $1",
+        "SELECT id, org_id, name, shared_identity_context, allow_impersonation, allow_dynamic_registration FROM projects WHERE id = $1",
         member.project_id
     )
     .fetch_one(&state.pool)
@@ -467,6 +602,8 @@ async fn get_project_handler(
             org_id: record.org_id.to_string(),
             name: record.name,
             shared_identity_context: record.shared_identity_context,
+            allow_impersonation: record.allow_impersonation,
+            allow_dynamic_registration: record.allow_dynamic_registration,
         }),
     ))
 }
@@ -491,37 +628,40 @@ async fn get_project_handler(
     )
 )]
 async fn create_application_handler(
-    member: ProjectMember,
+    member: RequirePermission<perm::AppCreate, ProjectMember>,
     State(state): State<AppState>,
     Json(body): Json<CreateApplicationRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
-    if body.redirect_uris.is_empty() {
-        let mut errors = HashMap::new();
-        errors.insert("redirect_uris".to_string(), vec!["empty".to_string()]);
+    let mut errors = HashMap::new();
+    applications::redirect_uri_errors(&body.redirect_uris, &mut errors);
+    if !errors.is_empty() {
         return Err(AppError::ValidationError(ValidationErrors::new(errors)));
     }
 
     let client_id = id::new_uuid();
     let application_id = id::new_uuid();
-    let raw_client_secret = crypto::generate_client_secret();
-    let client_secret_hash = crypto::hash_password(&raw_client_secret)?;
+
+    let mut tx = state.pool.begin().await?;
 
     sqlx::query!(
-        "INSERT INTO applications (id, project_id, name, client_id, client_secret_hash, redirect_uris) VALUES ($1, $2, $3, $4, $5, $6)",
+        "INSERT INTO applications (id, project_id, name, client_id, redirect_uris) VALUES ($1, $2, $3, $4, $5)",
         application_id,
         member.project_id,
         body.name,
         client_id,
-        client_secret_hash,
         &body.redirect_uris,
     )
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
+    let secret = application_secrets::insert_secret(&mut *tx, application_id, None).await?;
+
+    tx.commit().await?;
+
     Ok((
         StatusCode::CREATED,
         Json(ApplicationsResponse {
-            raw_client_secret,
+            raw_client_secret: secret.client_secret,
             client_id: client_id.to_string(),
         }),
     ))
@@ -543,11 +683,11 @@ async fn create_application_handler(
     )
 )]
 async fn list_applications_handler(
-    member: ProjectMember,
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let records = sqlx::query!(
-        "SELECT id, name, client_id FROM applications WHERE project_id = $1 ORDER BY name",
+        "SELECT id, name, client_id, first_party FROM applications WHERE project_id = $1 AND deleted_at IS NULL ORDER BY name",
         member.project_id
     )
     .fetch_all(&state.pool)
@@ -559,6 +699,7 @@ async fn list_applications_handler(
             id: r.id.to_string(),
             name: r.name,
             client_id: r.client_id.to_string(),
+            first_party: r.first_party,
         })
         .collect();
 
@@ -568,7 +709,7 @@ async fn list_applications_handler(
 /// Path params struct for handlers that need `app_id` in addition to what
 /// `ProjectMember` already extracts from `org_id` and `project_id`.
 #[derive(Deserialize)]
-struct AppIdPath {
+pub struct AppIdPath {
     app_id: String,
 }
 
@@ -588,40 +729,38 @@ struct AppIdPath {
         (status = 400, description = "Validation error"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
     )
 )]
 async fn applications_scopes_handler(
-    member: ProjectMember,
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
     Path(AppIdPath { app_id }): Path<AppIdPath>,
     State(state): State<AppState>,
     Json(body): Json<ApplicationScopesRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
     let app_id = id::parse_uuid(&app_id)?;
 
-    if body.application_scopes.is_empty()
-        || body
-            .application_scopes
-            .iter()
-            .any(|s| s.description.is_empty() || s.name.is_empty())
-    {
-        let mut errors = HashMap::new();
-        errors.insert("application_scopes".to_string(), vec!["empty or something".to_string()]);
+    let mut errors = HashMap::new();
+    if body.application_scopes.is_empty() {
+        errors.insert("application_scopes".to_string(), vec!["must not be empty".to_string()]);
+    }
+    for (i, scope) in body.application_scopes.iter().enumerate() {
+        let name_errors = permissions::name_errors(&scope.name);
+        if !name_errors.is_empty() {
+            errors.insert(format!("application_scopes[{i}].name"), name_errors);
+        }
+        if scope.description.is_empty() {
+            errors.insert(
+                format!("application_scopes[{i}].description"),
+                vec!["must not be empty".to_string()],
+            );
+        }
+    }
+    if !errors.is_empty() {
         return Err(AppError::ValidationError(ValidationErrors::new(errors)));
     }
 
-    // Verify the application belongs to this project.
-    let in_project: bool = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM applications WHERE id = $1 AND project_id = $2)",
-        app_id,
-        member.project_id,
-    )
-    .fetch_one(&state.pool)
-    .await?
-    .unwrap_or(false);
-
-    if !in_project {
-        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
-    }
+    permissions::application_in_project(&state, &member, app_id).await?;
 
     let scopes_len = body.application_scopes.len();
     let mut permission_ids = Vec::with_capacity(scopes_len);
@@ -663,13 +802,13 @@ async fn applications_scopes_handler(
     security(("bearer_auth" = [])),
     params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
     responses(
-        (status = 200, description = "Org-scoped metrics", body = MetricsResponse),
+        (status = 200, description = "Org-scoped metrics; members of only some projects see those projects' metrics", body = MetricsResponse),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
     )
 )]
 async fn metrics_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::MetricsRead>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
     let total_requests_24h = sqlx::query!(
@@ -678,10 +817,11 @@ async fn metrics_handler(
             FROM auth_events ae
             JOIN applications a ON ae.application_id = a.id
             JOIN projects p ON a.project_id = p.id
-            WHERE p.org_id = $1
+            WHERE p.org_id = $1 AND ($2::uuid[] IS NULL OR p.id = ANY($2))
               AND ae.occurred_at >= NOW() - INTERVAL '24 hours'
         "#,
         member.org_id,
+        member.projects.as_deref(),
     )
     .fetch_one(&state.pool)
     .await?
@@ -693,9 +833,11 @@ async fn metrics_handler(
             SELECT COUNT(*)::bigint AS active_applications
             FROM applications a
             JOIN projects p ON a.project_id = p.id
-            WHERE p.org_id = $1
+            WHERE p.org_id = $1 AND p.deleted_at IS NULL AND a.deleted_at IS NULL
+              AND ($2::uuid[] IS NULL OR p.id = ANY($2))
         "#,
         member.org_id,
+        member.projects.as_deref(),
     )
     .fetch_one(&state.pool)
     .await?
@@ -708,11 +850,12 @@ async fn metrics_handler(
             FROM auth_events ae
             JOIN applications a ON ae.application_id = a.id
             JOIN projects p ON a.project_id = p.id
-            WHERE p.org_id = $1
+            WHERE p.org_id = $1 AND ($2::uuid[] IS NULL OR p.id = ANY($2))
               AND ae.occurred_at >= NOW() - INTERVAL '24 hours'
               AND ae.success = false
         "#,
         member.org_id,
+        member.projects.as_deref(),
     )
     .fetch_one(&state.pool)
     .await?
@@ -740,14 +883,14 @@ async fn metrics_handler(
         CursorParams,
     ),
     responses(
-        (status = 200, description = "Org-scoped admin logs", body = CursorPage<AdminLogItem>),
+        (status = 200, description = "Org-scoped admin logs; members of only some projects see those projects' logs", body = CursorPage<AdminLogItem>),
         (status = 400, description = "Invalid cursor"),
         (status = 401, description = "Unauthorized"),
         (status = 403, description = "Forbidden"),
     )
 )]
 async fn logs_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::LogsRead>,
     State(state): State<AppState>,
     Query(params): Query<CursorParams>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -762,7 +905,7 @@ async fn logs_handler(
                 FROM auth_events ae
                 JOIN applications a ON ae.application_id = a.id
                 JOIN projects p ON a.project_id = p.id
-                WHERE p.org_id = $1
+                WHERE p.org_id = $1 AND ($5::uuid[] IS NULL OR p.id = ANY($5))
                   AND (ae.occurred_at, ae.id) < ($2, $3)
                 ORDER BY ae.occurred_at DESC, ae.id DESC
                 LIMIT $4
@@ -771,6 +914,7 @@ async fn logs_handler(
             cursor_time,
             cursor_id,
             limit + 1,
+            member.projects.as_deref(),
         )
         .fetch_all(&state.pool)
         .await?
@@ -793,12 +937,13 @@ async fn logs_handler(
                 FROM auth_events ae
                 JOIN applications a ON ae.application_id = a.id
                 JOIN projects p ON a.project_id = p.id
-                WHERE p.org_id = $1
+                WHERE p.org_id = $1 AND ($3::uuid[] IS NULL OR p.id = ANY($3))
                 ORDER BY ae.occurred_at DESC, ae.id DESC
                 LIMIT $2
             "#,
             member.org_id,
             limit + 1,
+            member.projects.as_deref(),
         )
         .fetch_all(&state.pool)
         .await?
@@ -0,0 +1,241 @@
+use super::users::{AccountIdPath, account_identity};
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct AccountScopesRequestBody {
+    /// Application whose permissions are granted or revoked.
+    app_id: String,
+    /// Permission names defined on the application.
+    scopes: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountScopeItem {
+    app_id: String,
+    application_name: String,
+    name: String,
+    description: Option<String>,
+    /// Whether the scope is granted directly through `account_scopes`.
+    direct: bool,
+    /// Names of the assigned roles that bundle this scope.
+    roles: Vec<String>,
+}
+
+/// Resolves permission names of an application in the member's project into permission ids.
+/// Every name must exist; unknown ones are reported back as a validation error.
+pub(super) async fn permission_ids(
+    state: &AppState,
+    member: &ProjectMember,
+    app_id: &str,
+    scopes: &[String],
+) -> Result<Vec<uuid::Uuid>, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+
+    if scopes.is_empty() {
+        let mut errors = HashMap::new();
+        errors.insert("scopes".to_string(), vec!["must not be empty".to_string()]);
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let in_project: bool = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM applications WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL)",
+        app_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?
+    .unwrap_or(false);
+
+    if !in_project {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    let found = sqlx::query!(
+        "SELECT id, name FROM permissions WHERE app_id = $1 AND name = ANY($2)",
+        app_id,
+        scopes,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let unknown: Vec<String> = scopes
+        .iter()
+        .filter(|name| !found.iter().any(|p| &p.name == *name))
+        .map(|name| format!("unknown scope '{name}'"))
+        .collect();
+
+    if !unknown.is_empty() {
+        let mut errors = HashMap::new();
+        errors.insert("scopes".to_string(), unknown);
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    Ok(found.into_iter().map(|p| p.id).collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/scopes",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Scopes granted to the account directly or through roles, across the project's applications", body = Vec<AccountScopeItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account not found in this project"),
+    )
+)]
+pub async fn list_account_scopes_handler(
+    member: RequirePermission<perm::UsersRead, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    let records = sqlx::query!(
+        r#"
+            WITH granted AS (
+                SELECT permission_id, NULL::text AS role_name
+                FROM account_scopes
+                WHERE account_id = $1
+                UNION ALL
+                SELECT rp.permission_id, r.name
+                FROM account_roles ar
+                JOIN project_roles r ON r.id = ar.role_id
+                JOIN project_role_permissions rp ON rp.role_id = ar.role_id
+                WHERE ar.account_id = $1
+            )
+            SELECT a.id AS app_id, a.name AS application_name, p.name, p.description,
+                   bool_or(g.role_name IS NULL) AS "direct!",
+                   COALESCE(
+                       array_agg(g.role_name ORDER BY g.role_name) FILTER (WHERE g.role_name IS NOT NULL),
+                       '{}'
+                   ) AS "roles!"
+            FROM granted g
+            JOIN permissions p ON p.id = g.permission_id
+            JOIN applications a ON a.id = p.app_id
+            GROUP BY a.id, a.name, p.id, p.name, p.description
+            ORDER BY a.name, p.name
+        "#,
+        account_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let scopes: Vec<AccountScopeItem> = records
+        .into_iter()
+        .map(|r| AccountScopeItem {
+            app_id: r.app_id.to_string(),
+            application_name: r.application_name,
+            name: r.name,
+            description: r.description,
+            direct: r.direct,
+            roles: r.roles,
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(scopes)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/scopes/grant",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    request_body = AccountScopesRequestBody,
+    responses(
+        (status = 204, description = "Scopes granted; already granted ones are left untouched"),
+        (status = 400, description = "Validation error or unknown scope"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account or application not found in this project"),
+    )
+)]
+pub async fn grant_account_scopes_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<AccountScopesRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    let permission_ids = permission_ids(&state, &member, &body.app_id, &body.scopes).await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO account_scopes (account_id, permission_id)
+            SELECT $1, permission_id FROM UNNEST($2::uuid[]) AS t(permission_id)
+            ON CONFLICT DO NOTHING
+        "#,
+        account_id,
+        &permission_ids,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/scopes/revoke",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    request_body = AccountScopesRequestBody,
+    responses(
+        (status = 204, description = "Scopes revoked; scopes that were not granted are ignored"),
+        (status = 400, description = "Validation error or unknown scope"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account or application not found in this project"),
+    )
+)]
+pub async fn revoke_account_scopes_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<AccountScopesRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    let permission_ids = permission_ids(&state, &member, &body.app_id, &body.scopes).await?;
+
+    sqlx::query!(
+        "DELETE FROM account_scopes WHERE account_id = $1 AND permission_id = ANY($2)",
+        account_id,
+        &permission_ids,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,308 @@
+use crate::admin::authorization::{AdminId, RequirePermission, Role};
+use crate::admin::policy::{Permission, perm};
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Serialize, ToSchema)]
+pub struct PermissionCatalogItem {
+    name: Permission,
+    description: &'static str,
+    /// Held by owners only; the `admin` role and custom roles never grant it.
+    owner_only: bool,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AdminRoleRequestBody {
+    #[validate(length(min = 1, max = 50, message = "Should have from 1 to 50 characters"))]
+    name: String,
+    description: Option<String>,
+    /// Catalog permission names, e.g. `app.secret.rotate`. Owner-only permissions are rejected.
+    #[serde(default)]
+    permissions: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AdminRoleResponse {
+    id: String,
+    name: String,
+    description: Option<String>,
+    permissions: Vec<Permission>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct AdminRoleIdPath {
+    role_id: String,
+}
+
+/// Checks what `validate` cannot: the name must not shadow a built-in role, and every
+/// permission must be in the catalog and grantable to non-owners. Returns the canonical names.
+fn role_permissions(body: &AdminRoleRequestBody) -> Result<Vec<String>, AppError> {
+    let mut errors = HashMap::new();
+    if Role::parse(&body.name).is_some() {
+        errors.insert(
+            "name".to_string(),
+            vec!["'owner' and 'admin' are built-in roles".to_string()],
+        );
+    }
+
+    let mut names = Vec::new();
+    for (i, name) in body.permissions.iter().enumerate() {
+        match Permission::parse(name) {
+            Some(permission) if permission.owner_only() => {
+                errors.insert(format!("permissions[{i}]"), vec!["is reserved to owners".to_string()]);
+            }
+            Some(permission) => {
+                if !names.contains(&permission.as_str().to_string()) {
+                    names.push(permission.as_str().to_string());
+                }
+            }
+            None => {
+                errors.insert(
+                    format!("permissions[{i}]"),
+                    vec!["is not in the permission catalog".to_string()],
+                );
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+    Ok(names)
+}
+
+async fn load_roles(state: &AppState, org_id: Uuid, role_id: Option<Uuid>) -> Result<Vec<AdminRoleResponse>, AppError> {
+    let roles = sqlx::query!(
+        r#"
+            SELECT id, name, description, permissions, created_at
+            FROM admin_org_roles
+            WHERE org_id = $1 AND ($2::uuid IS NULL OR id = $2)
+            ORDER BY name
+        "#,
+        org_id,
+        role_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| AdminRoleResponse {
+        id: r.id.to_string(),
+        name: r.name,
+        description: r.description,
+        permissions: r
+            .permissions
+            .iter()
+            .filter_map(|name| Permission::parse(name))
+            .collect(),
+        created_at: r.created_at,
+    })
+    .collect();
+
+    Ok(roles)
+}
+
+async fn load_role(state: &AppState, org_id: Uuid, role_id: Uuid) -> Result<AdminRoleResponse, AppError> {
+    load_roles(state, org_id, Some(role_id))
+        .await?
+        .pop()
+        .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))
+}
+
+#[utoipa::path(
+    get,
+    path = "/permissions",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Every permission an admin role can grant", body = Vec<PermissionCatalogItem>),
+        (status = 401, description = "Unauthorized"),
+    )
+)]
+pub async fn list_permission_catalog_handler(_admin: AdminId) -> impl IntoResponse {
+    let catalog: Vec<PermissionCatalogItem> = Permission::ALL
+        .iter()
+        .map(|permission| PermissionCatalogItem {
+            name: *permission,
+            description: permission.description(),
+            owner_only: permission.owner_only(),
+        })
+        .collect();
+
+    (StatusCode::OK, Json(catalog))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/roles",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    request_body = AdminRoleRequestBody,
+    responses(
+        (status = 201, description = "Custom role created", body = AdminRoleResponse),
+        (status = 400, description = "Validation error or unknown permission"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks roles.manage"),
+        (status = 409, description = "A role with this name already exists in the organization"),
+    )
+)]
+pub async fn create_admin_role_handler(
+    member: RequirePermission<perm::RolesManage>,
+    State(state): State<AppState>,
+    Json(body): Json<AdminRoleRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let permissions = role_permissions(&body)?;
+    let role_id = id::new_uuid();
+
+    sqlx::query!(
+        "INSERT INTO admin_org_roles (id, org_id, name, description, permissions) VALUES ($1, $2, $3, $4, $5)",
+        role_id,
+        member.org_id,
+        body.name,
+        body.description,
+        &permissions,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    let role = load_role(&state, member.org_id, role_id).await?;
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/roles",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 200, description = "Custom roles of the organization", body = Vec<AdminRoleResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_admin_roles_handler(
+    member: RequirePermission<perm::OrgRead>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let roles = load_roles(&state, member.org_id, None).await?;
+    Ok((StatusCode::OK, Json(roles)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orgs/{org_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Custom role ID (UUID v7)"),
+    ),
+    request_body = AdminRoleRequestBody,
+    responses(
+        (status = 200, description = "Role replaced; members holding it get the new permissions on their next request", body = AdminRoleResponse),
+        (status = 400, description = "Validation error or unknown permission"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks roles.manage"),
+        (status = 404, description = "Role not found in this organization"),
+        (status = 409, description = "A role with this name already exists in the organization"),
+    )
+)]
+pub async fn update_admin_role_handler(
+    member: RequirePermission<perm::RolesManage>,
+    Path(AdminRoleIdPath { role_id }): Path<AdminRoleIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<AdminRoleRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let role_id = id::parse_uuid(&role_id)?;
+    body.validate()?;
+    let permissions = role_permissions(&body)?;
+
+    sqlx::query!(
+        r#"
+            UPDATE admin_org_roles SET name = $3, description = $4, permissions = $5
+            WHERE id = $1 AND org_id = $2
+            RETURNING id
+        "#,
+        role_id,
+        member.org_id,
+        body.name,
+        body.description,
+        &permissions,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    let role = load_role(&state, member.org_id, role_id).await?;
+    Ok((StatusCode::OK, Json(role)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Custom role ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Role deleted"),
+        (status = 400, description = "The role is still assigned to members"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks roles.manage"),
+        (status = 404, description = "Role not found in this organization"),
+    )
+)]
+pub async fn delete_admin_role_handler(
+    member: RequirePermission<perm::RolesManage>,
+    Path(AdminRoleIdPath { role_id }): Path<AdminRoleIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let role_id = id::parse_uuid(&role_id)?;
+
+    let assigned = sqlx::query_scalar!(
+        r#"
+            SELECT (SELECT COUNT(*) FROM admin_org_memberships WHERE custom_role_id = $1)
+                 + (SELECT COUNT(*) FROM admin_project_memberships WHERE custom_role_id = $1) AS "count!"
+        "#,
+        role_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    if assigned > 0 {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "role_id".to_string(),
+            vec![format!(
+                "is assigned to {assigned} member(s); give them another role first"
+            )],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    sqlx::query!(
+        "DELETE FROM admin_org_roles WHERE id = $1 AND org_id = $2 RETURNING id",
+        role_id,
+        member.org_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
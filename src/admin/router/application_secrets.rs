@@ -0,0 +1,247 @@
+use super::AppIdPath;
+use super::permissions::application_in_project;
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::error::{AppError, ValidationErrors};
+use crate::router::AppState;
+use crate::{crypto, id};
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateSecretRequestBody {
+    /// When the secret stops being accepted. Omit for a secret that lives until it is deleted.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateSecretResponse {
+    id: String,
+    /// Shown once; only its hash is stored.
+    pub(crate) client_secret: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SecretItem {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_used_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct SecretPath {
+    app_id: String,
+    secret_id: String,
+}
+
+/// Generates a secret for the application, stores its hash and returns the raw value with its row.
+pub(crate) async fn insert_secret(
+    executor: impl PgExecutor<'_>,
+    application_id: Uuid,
+    expires_at: Option<OffsetDateTime>,
+) -> Result<CreateSecretResponse, AppError> {
+    let secret_id = id::new_uuid();
+    let client_secret = crypto::generate_client_secret();
+    let secret_hash = crypto::hash_password(&client_secret)?;
+
+    let created_at = sqlx::query_scalar!(
+        r#"
+            INSERT INTO application_secrets (id, application_id, secret_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING created_at
+        "#,
+        secret_id,
+        application_id,
+        secret_hash,
+        expires_at,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(CreateSecretResponse {
+        id: secret_id.to_string(),
+        client_secret,
+        created_at,
+        expires_at,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/secrets",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    request_body = CreateSecretRequestBody,
+    responses(
+        (status = 201, description = "Secret minted; existing secrets stay valid until deleted or expired", body = CreateSecretResponse),
+        (status = 400, description = "Expiry is in the past"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+    )
+)]
+pub async fn create_secret_handler(
+    member: RequirePermission<perm::AppSecretRotate, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<CreateSecretRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+
+    if body
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    {
+        let mut errors = HashMap::new();
+        errors.insert("expires_at".to_string(), vec!["must be in the future".to_string()]);
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    application_in_project(&state, &member, app_id).await?;
+
+    let secret = insert_secret(&state.pool, app_id, body.expires_at).await?;
+    Ok((StatusCode::CREATED, Json(secret)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/secrets",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Secrets of the application, newest first, without their values", body = Vec<SecretItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+    )
+)]
+pub async fn list_secrets_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    application_in_project(&state, &member, app_id).await?;
+
+    let secrets: Vec<SecretItem> = sqlx::query!(
+        r#"
+            SELECT id, created_at, expires_at, last_used_at
+            FROM application_secrets
+            WHERE application_id = $1
+            ORDER BY created_at DESC, id DESC
+        "#,
+        app_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| SecretItem {
+        id: r.id.to_string(),
+        created_at: r.created_at,
+        expires_at: r.expires_at,
+        last_used_at: r.last_used_at,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(secrets)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/secrets/{secret_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+        ("secret_id" = String, Path, description = "Secret ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Secret retired"),
+        (status = 400, description = "The secret is the application's last active one"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application or secret not found in this project"),
+    )
+)]
+pub async fn delete_secret_handler(
+    member: RequirePermission<perm::AppSecretRotate, ProjectMember>,
+    Path(SecretPath { app_id, secret_id }): Path<SecretPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    let secret_id = id::parse_uuid(&secret_id)?;
+    application_in_project(&state, &member, app_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Lock the application's secrets so two concurrent deletes cannot both pass the check below.
+    let active = sqlx::query!(
+        r#"
+            SELECT id, (expires_at IS NULL OR expires_at > NOW()) AS "active!"
+            FROM application_secrets
+            WHERE application_id = $1
+            FOR UPDATE
+        "#,
+        app_id,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let target = active
+        .iter()
+        .find(|s| s.id == secret_id)
+        .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+
+    // Rotation mints the replacement first; removing the last active secret would lock the application out.
+    if target.active && active.iter().filter(|s| s.active).count() == 1 {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "secret_id".to_string(),
+            vec!["cannot delete the last active secret; create a new one first".to_string()],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    sqlx::query!("DELETE FROM application_secrets WHERE id = $1", secret_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,272 @@
+use super::AppIdPath;
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::admin::retention;
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use validator::ValidateUrl;
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateApplicationRequestBody {
+    name: Option<String>,
+    /// Replaces the whole list.
+    redirect_uris: Option<Vec<String>>,
+    /// `false` makes users approve the application's scopes on the consent page before it gets tokens.
+    first_party: Option<bool>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ApplicationResponse {
+    id: String,
+    name: String,
+    client_id: String,
+    redirect_uris: Vec<String>,
+    /// Whether tokens are issued without asking the user for consent.
+    first_party: bool,
+}
+
+/// Per-field errors for a list of redirect URIs, keyed like `redirect_uris[1]`.
+pub(super) fn redirect_uri_errors(redirect_uris: &[String], errors: &mut HashMap<String, Vec<String>>) {
+    if redirect_uris.is_empty() {
+        errors.insert("redirect_uris".to_string(), vec!["empty".to_string()]);
+    }
+    for (i, uri) in redirect_uris.iter().enumerate() {
+        if !uri.validate_url() {
+            errors.insert(
+                format!("redirect_uris[{i}]"),
+                vec![format!("'{uri}' is not a valid URL")],
+            );
+        }
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    request_body = UpdateApplicationRequestBody,
+    responses(
+        (status = 200, description = "Application updated", body = ApplicationResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+    )
+)]
+pub async fn update_application_handler(
+    member: RequirePermission<perm::AppUpdate, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdateApplicationRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+
+    let mut errors = HashMap::new();
+    if body.name.is_none() && body.redirect_uris.is_none() && body.first_party.is_none() {
+        errors.insert(
+            "name".to_string(),
+            vec!["name, redirect_uris or first_party is required".to_string()],
+        );
+    }
+    if body.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+        errors.insert("name".to_string(), vec!["must not be empty".to_string()]);
+    }
+    if let Some(redirect_uris) = &body.redirect_uris {
+        redirect_uri_errors(redirect_uris, &mut errors);
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let record = sqlx::query!(
+        r#"
+            UPDATE applications
+            SET name = COALESCE($3, name), redirect_uris = COALESCE($4, redirect_uris),
+                first_party = COALESCE($5, first_party)
+            WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL
+            RETURNING id, name, client_id, redirect_uris, first_party
+        "#,
+        app_id,
+        member.project_id,
+        body.name,
+        body.redirect_uris.as_deref(),
+        body.first_party,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ApplicationResponse {
+            id: record.id.to_string(),
+            name: record.name,
+            client_id: record.client_id.to_string(),
+            redirect_uris: record.redirect_uris,
+            first_party: record.first_party,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Application disabled; it can be restored within the restore window"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+    )
+)]
+pub async fn delete_application_handler(
+    member: RequirePermission<perm::AppDelete, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+
+    // Tokens stop being issued and accepted at once: every client_id lookup skips deleted applications.
+    let deleted = sqlx::query!(
+        "UPDATE applications SET deleted_at = NOW() WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL",
+        app_id,
+        member.project_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/restore",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Application restored", body = ApplicationResponse),
+        (status = 400, description = "The restore window has passed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No deleted application with this ID in this project"),
+    )
+)]
+pub async fn restore_application_handler(
+    member: RequirePermission<perm::AppDelete, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+
+    let deleted_at = sqlx::query_scalar!(
+        r#"
+            SELECT deleted_at AS "deleted_at!"
+            FROM applications
+            WHERE id = $1 AND project_id = $2 AND deleted_at IS NOT NULL
+        "#,
+        app_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    retention::ensure_restorable("app_id", deleted_at)?;
+
+    let record = sqlx::query!(
+        r#"
+            UPDATE applications SET deleted_at = NULL
+            WHERE id = $1 AND project_id = $2
+            RETURNING id, name, client_id, redirect_uris, first_party
+        "#,
+        app_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ApplicationResponse {
+            id: record.id.to_string(),
+            name: record.name,
+            client_id: record.client_id.to_string(),
+            redirect_uris: record.redirect_uris,
+            first_party: record.first_party,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/purge",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Application and its grants erased; its auth events are kept without the link"),
+        (status = 400, description = "The application can still be restored"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Only owners can purge"),
+        (status = 404, description = "No deleted application with this ID in this project"),
+    )
+)]
+pub async fn purge_application_handler(
+    member: RequirePermission<perm::AppPurge, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    let project_id = member.project_id;
+
+    let deleted_at = sqlx::query_scalar!(
+        r#"
+            SELECT deleted_at AS "deleted_at!"
+            FROM applications
+            WHERE id = $1 AND project_id = $2 AND deleted_at IS NOT NULL
+        "#,
+        app_id,
+        project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    retention::ensure_purgeable("app_id", deleted_at)?;
+
+    sqlx::query!("DELETE FROM applications WHERE id = $1", app_id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,3 +1,4 @@
+use crate::audit::{self, AuthEvent};
 use crate::error::AppError;
 use crate::router::AppState;
 use crate::{crypto, id, jwt};
@@ -20,8 +21,8 @@ pub struct RegisterAdminRequestBody {
 
 #[derive(Serialize, ToSchema)]
 pub struct RegisterAdminResponse {
-    user_id: String,
-    access_token: String,
+    pub(super) user_id: String,
+    pub(super) access_token: String,
 }
 
 #[utoipa::path(
@@ -53,16 +54,16 @@ pub async fn register_admin_handler(
     .execute(&state.pool)
     .await?;
 
-    write_auth_event(
-        &state,
-        "admin_register",
-        true,
-        "/admin/register",
-        Some(user_id),
-        None,
-        None,
-        None,
-        Some(201),
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "admin_register",
+            success: true,
+            route: "/admin/register",
+            admin_user_id: Some(user_id),
+            http_status: Some(201),
+            ..Default::default()
+        },
     )
     .await?;
 
@@ -105,23 +106,23 @@ pub async fn login_admin_handler(
     body.validate()?;
 
     let record = sqlx::query!(
-        "SELECT username, password_hash, id FROM admin_users WHERE username = $1",
+        r#"SELECT username, password_hash AS "password_hash!", id FROM admin_users WHERE username = $1 AND kind = 'human'"#,
         body.username
     )
     .fetch_optional(&state.pool)
     .await?;
 
     let Some(record) = record else {
-        write_auth_event(
-            &state,
-            "admin_login",
-            false,
-            "/admin/login",
-            None,
-            None,
-            None,
-            Some(body.username.as_str()),
-            Some(404),
+        audit::write_auth_event(
+            &state.pool,
+            AuthEvent {
+                event_type: "admin_login",
+                success: false,
+                route: "/admin/login",
+                identifier: Some(body.username.as_str()),
+                http_status: Some(404),
+                ..Default::default()
+            },
         )
         .await?;
         return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
@@ -129,31 +130,33 @@ pub async fn login_admin_handler(
 
     if crypto::verify_password(body.password.as_ref(), record.password_hash.as_ref()).is_err() {
         error!("Invalid hash password for admin user {}", body.username);
-        write_auth_event(
-            &state,
-            "admin_login",
-            false,
-            "/admin/login",
-            Some(record.id),
-            None,
-            None,
-            Some(body.username.as_str()),
-            Some(401),
+        audit::write_auth_event(
+            &state.pool,
+            AuthEvent {
+                event_type: "admin_login",
+                success: false,
+                route: "/admin/login",
+                admin_user_id: Some(record.id),
+                identifier: Some(body.username.as_str()),
+                http_status: Some(401),
+                ..Default::default()
+            },
         )
         .await?;
         return Err(AppError::InvalidToken);
     }
 
-    write_auth_event(
-        &state,
-        "admin_login",
-        true,
-        "/admin/login",
-        Some(record.id),
-        None,
-        None,
-        Some(body.username.as_str()),
-        Some(200),
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "admin_login",
+            success: true,
+            route: "/admin/login",
+            admin_user_id: Some(record.id),
+            identifier: Some(body.username.as_str()),
+            http_status: Some(200),
+            ..Default::default()
+        },
     )
     .await?;
 
@@ -162,33 +165,3 @@ pub async fn login_admin_handler(
 
     Ok((StatusCode::OK, Json(response)).into_response())
 }
-
-async fn write_auth_event(
-    state: &AppState,
-    event_type: &str,
-    success: bool,
-    route: &str,
-    admin_user_id: Option<uuid::Uuid>,
-    application_id: Option<uuid::Uuid>,
-    application_name: Option<&str>,
-    identifier: Option<&str>,
-    http_status: Option<i32>,
-) -> Result<(), AppError> {
-    sqlx::query!(
-        "INSERT INTO auth_events (id, event_type, success, route, admin_user_id, application_id, application_name, identifier, http_status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-        id::new_uuid(),
-        event_type,
-        success,
-        route,
-        admin_user_id,
-        application_id,
-        application_name,
-        identifier,
-        http_status,
-    )
-    .execute(&state.pool)
-    .await?;
-
-    Ok(())
-}
-
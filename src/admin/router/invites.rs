@@ -1,29 +1,96 @@
-use crate::admin::authorization::AdminId;
+use super::auth::RegisterAdminResponse;
+use super::ownership;
+use crate::admin::authorization::SessionAdmin;
+use crate::audit::{self, AuthEvent};
 use crate::error::{AppError, ValidationErrors};
+use crate::pagination::{self, CursorPage, CursorParams};
 use crate::router::AppState;
-use crate::{id, admin::authorization::{OrgMember, ProjectMember}};
-use axum::extract::{Path, State};
+use crate::{
+    admin::authorization::{ProjectMember, RequirePermission},
+    admin::policy::perm,
+    id,
+};
+use crate::{crypto, jwt};
+use axum::Json;
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::Json;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::{Validate, ValidateEmail};
 
+/// Exactly one of `invitee_username` and `invitee_email` is required.
 #[derive(Deserialize, ToSchema)]
 pub struct CreateOrgInviteRequestBody {
-    invitee_username: String,
+    /// Username of a registered admin.
+    invitee_username: Option<String>,
+    /// Address of someone without an admin account; they register through the invite link.
+    invitee_email: Option<String>,
     role: String,
 }
 
+/// Exactly one of `invitee_username` and `invitee_email` is required.
 #[derive(Deserialize, ToSchema)]
 pub struct CreateProjectInviteRequestBody {
-    invitee_username: String,
+    /// Username of a registered admin.
+    invitee_username: Option<String>,
+    /// Address of someone without an admin account; they register through the invite link.
+    invitee_email: Option<String>,
     role: String,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct InviteResponse {
+    pub(super) id: String,
+    /// Signed invite link token for email invites, to be delivered to the invitee. It is not
+    /// stored and is only returned here.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) invite_token: Option<String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RegisterWithInviteRequestBody {
+    /// Token from the invite link.
+    invite_token: String,
+    #[validate(length(min = 6, max = 50, message = "Should have from 6 to 50 characters"))]
+    username: String,
+    #[validate(length(min = 6, max = 50, message = "Should have from 6 to 50 characters"))]
+    password: String,
+}
+
+const INVITE_STATUSES: [&str; 5] = ["pending", "accepted", "declined", "expired", "revoked"];
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct InviteFilterParams {
+    /// One of `pending`, `accepted`, `declined`, `expired`, `revoked`. Pending invites past
+    /// their expiry are reported as `expired`.
+    status: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct InviteListItem {
     id: String,
+    /// `membership` or `ownership_transfer`.
+    kind: String,
+    org_id: Option<String>,
+    project_id: Option<String>,
+    /// Name of the org or project the invite is for.
+    scope_name: String,
+    /// Unset for email invites until the invitee registers.
+    invitee_username: Option<String>,
+    invitee_email: Option<String>,
+    inviter_username: String,
+    role: String,
+    status: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: time::OffsetDateTime,
 }
 
 fn validate_role(role: &str) -> Result<(), AppError> {
@@ -35,10 +102,146 @@ fn validate_role(role: &str) -> Result<(), AppError> {
     Ok(())
 }
 
-fn invite_expires_at() -> time::OffsetDateTime {
+pub(super) fn invite_expires_at() -> time::OffsetDateTime {
     time::OffsetDateTime::now_utc() + time::Duration::days(7)
 }
 
+/// Who an invite is addressed to.
+enum Invitee {
+    Username(String),
+    Email(String),
+}
+
+fn parse_invitee(username: Option<String>, email: Option<String>) -> Result<Invitee, AppError> {
+    let mut errors = HashMap::new();
+    let invitee = match (username, email) {
+        (Some(username), None) => Some(Invitee::Username(username)),
+        (None, Some(email)) if email.validate_email() => Some(Invitee::Email(email.trim().to_lowercase())),
+        (None, Some(_)) => {
+            errors.insert(
+                "invitee_email".to_string(),
+                vec!["must be a valid email address".to_string()],
+            );
+            None
+        }
+        _ => {
+            errors.insert(
+                "invitee_username".to_string(),
+                vec!["exactly one of invitee_username and invitee_email is required".to_string()],
+            );
+            None
+        }
+    };
+    invitee.ok_or_else(|| AppError::ValidationError(ValidationErrors::new(errors)))
+}
+
+/// Stores a membership invite for an org or a project and, for email invites, signs the link
+/// that binds the invite's address, scope and role.
+async fn create_invite(
+    state: &AppState,
+    invited_by: Uuid,
+    org_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    invitee: Invitee,
+    role: &str,
+) -> Result<InviteResponse, AppError> {
+    validate_role(role)?;
+
+    let invite_id = id::new_uuid();
+    let expires_at = invite_expires_at();
+    let (invitee_username, invitee_email) = match invitee {
+        Invitee::Username(username) => (Some(username), None),
+        Invitee::Email(email) => (None, Some(email)),
+    };
+
+    // Service accounts only ever belong to the org that owns them.
+    if let Some(username) = &invitee_username {
+        let is_service_account = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM admin_users WHERE username = $1 AND kind = 'service') AS "exists!""#,
+            username,
+        )
+        .fetch_one(&state.pool)
+        .await?;
+        if is_service_account {
+            let mut errors = HashMap::new();
+            errors.insert(
+                "invitee_username".to_string(),
+                vec!["service accounts cannot be invited".to_string()],
+            );
+            return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+        }
+    }
+
+    sqlx::query!(
+        r#"
+            INSERT INTO admin_invites
+                (id, invited_by_admin_user_id, org_id, project_id, invitee_username, invitee_email, role, status,
+                 expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 'pending', $8)
+        "#,
+        invite_id,
+        invited_by,
+        org_id,
+        project_id,
+        invitee_username,
+        invitee_email,
+        role,
+        expires_at,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    let invite_token = match invitee_email {
+        Some(email) => Some(jwt::generate_invite_token(&jwt::InviteTokenSubject {
+            invite_id: &invite_id.to_string(),
+            email: &email,
+            org_id: org_id.map(|id| id.to_string()).as_deref(),
+            project_id: project_id.map(|id| id.to_string()).as_deref(),
+            role,
+            expires_at,
+        })?),
+        None => None,
+    };
+
+    Ok(InviteResponse {
+        id: invite_id.to_string(),
+        invite_token,
+    })
+}
+
+/// Adds the membership an accepted invite grants.
+async fn add_membership(
+    tx: &mut Transaction<'_, Postgres>,
+    admin_id: Uuid,
+    org_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    role: &str,
+) -> Result<(), AppError> {
+    let membership_id = id::new_uuid();
+    if let Some(org_id) = org_id {
+        sqlx::query!(
+            "INSERT INTO admin_org_memberships (id, admin_user_id, org_id, role) VALUES ($1, $2, $3, $4)",
+            membership_id,
+            admin_id,
+            org_id,
+            role,
+        )
+        .execute(&mut **tx)
+        .await?;
+    } else if let Some(project_id) = project_id {
+        sqlx::query!(
+            "INSERT INTO admin_project_memberships (id, admin_user_id, project_id, role) VALUES ($1, $2, $3, $4)",
+            membership_id,
+            admin_id,
+            project_id,
+            role,
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
 #[utoipa::path(
     post,
     path = "/orgs/{org_id}/invites",
@@ -56,31 +259,14 @@ fn invite_expires_at() -> time::OffsetDateTime {
     )
 )]
 pub async fn create_org_invite_handler(
-    member: OrgMember,
+    member: RequirePermission<perm::MembersInvite>,
     State(state): State<AppState>,
     Json(body): Json<CreateOrgInviteRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
-    validate_role(&body.role)?;
+    let invitee = parse_invitee(body.invitee_username, body.invitee_email)?;
+    let response = create_invite(&state, member.admin_id, Some(member.org_id), None, invitee, &body.role).await?;
 
-    let invite_id = id::new_uuid();
-    let expires_at = invite_expires_at();
-
-    sqlx::query!(
-        r#"
-            INSERT INTO admin_invites (id, invited_by_admin_user_id, org_id, invitee_username, role, status, expires_at)
-            VALUES ($1, $2, $3, $4, $5, 'pending', $6)
-        "#,
-        invite_id,
-        member.admin_id,
-        member.org_id,
-        body.invitee_username,
-        body.role,
-        expires_at,
-    )
-    .execute(&state.pool)
-    .await?;
-
-    Ok((StatusCode::CREATED, Json(InviteResponse { id: invite_id.to_string() })))
+    Ok((StatusCode::CREATED, Json(response)))
 }
 
 #[utoipa::path(
@@ -101,31 +287,22 @@ pub async fn create_org_invite_handler(
     )
 )]
 pub async fn create_project_invite_handler(
-    member: ProjectMember,
+    member: RequirePermission<perm::MembersInvite, ProjectMember>,
     State(state): State<AppState>,
     Json(body): Json<CreateProjectInviteRequestBody>,
 ) -> Result<impl IntoResponse, AppError> {
-    validate_role(&body.role)?;
-
-    let invite_id = id::new_uuid();
-    let expires_at = invite_expires_at();
-
-    sqlx::query!(
-        r#"
-            INSERT INTO admin_invites (id, invited_by_admin_user_id, project_id, invitee_username, role, status, expires_at)
-            VALUES ($1, $2, $3, $4, $5, 'pending', $6)
-        "#,
-        invite_id,
+    let invitee = parse_invitee(body.invitee_username, body.invitee_email)?;
+    let response = create_invite(
+        &state,
         member.admin_id,
-        member.project_id,
-        body.invitee_username,
-        body.role,
-        expires_at,
+        None,
+        Some(member.project_id),
+        invitee,
+        &body.role,
     )
-    .execute(&state.pool)
     .await?;
 
-    Ok((StatusCode::CREATED, Json(InviteResponse { id: invite_id.to_string() })))
+    Ok((StatusCode::CREATED, Json(response)))
 }
 
 #[utoipa::path(
@@ -140,12 +317,12 @@ pub async fn create_project_invite_handler(
         (status = 204, description = "Invite accepted"),
         (status = 400, description = "Invite not pending or expired"),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Forbidden — caller is not the invitee"),
+        (status = 403, description = "Forbidden — caller is not the invitee, or used a personal access token"),
         (status = 404, description = "Invite not found"),
     )
 )]
 pub async fn accept_invite_handler(
-    AdminId { admin_id }: AdminId,
+    SessionAdmin { admin_id }: SessionAdmin,
     Path(invite_id): Path<String>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -154,7 +331,12 @@ pub async fn accept_invite_handler(
     let mut tx = state.pool.begin().await?;
 
     let invite = sqlx::query!(
-        "SELECT id, org_id, project_id, invitee_username, role, status, expires_at FROM admin_invites WHERE id = $1",
+        r#"
+            SELECT id, invited_by_admin_user_id, org_id, project_id, invitee_username, role, status, expires_at, kind
+            FROM admin_invites
+            WHERE id = $1
+            FOR UPDATE
+        "#,
         invite_id
     )
     .fetch_optional(&mut *tx)
@@ -171,7 +353,8 @@ pub async fn accept_invite_handler(
     .fetch_one(&mut *tx)
     .await?;
 
-    if invite.invitee_username != caller_username {
+    // Email invites are accepted through the invite link instead.
+    if invite.invitee_username.as_deref() != Some(caller_username.as_str()) {
         return Err(AppError::Forbidden);
     }
 
@@ -187,27 +370,18 @@ pub async fn accept_invite_handler(
         )));
     }
 
-    let membership_id = id::new_uuid();
-    if let Some(org_id) = invite.org_id {
-        sqlx::query!(
-            "INSERT INTO admin_org_memberships (id, admin_user_id, org_id, role) VALUES ($1, $2, $3, $4)",
-            membership_id,
+    if invite.kind == "ownership_transfer" {
+        ownership::complete_transfer(
+            &mut tx,
+            invite_id,
+            invite.invited_by_admin_user_id,
             admin_id,
-            org_id,
-            invite.role,
-        )
-        .execute(&mut *tx)
-        .await?;
-    } else if let Some(project_id) = invite.project_id {
-        sqlx::query!(
-            "INSERT INTO admin_project_memberships (id, admin_user_id, project_id, role) VALUES ($1, $2, $3, $4)",
-            membership_id,
-            admin_id,
-            project_id,
-            invite.role,
+            invite.org_id,
+            invite.project_id,
         )
-        .execute(&mut *tx)
         .await?;
+    } else {
+        add_membership(&mut tx, admin_id, invite.org_id, invite.project_id, &invite.role).await?;
     }
 
     sqlx::query!(
@@ -222,6 +396,113 @@ pub async fn accept_invite_handler(
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/register/invite",
+    tag = "admin",
+    request_body = RegisterWithInviteRequestBody,
+    responses(
+        (status = 201, description = "Admin registered and invite accepted", body = RegisterAdminResponse),
+        (status = 400, description = "Validation error, or the invite is no longer pending"),
+        (status = 401, description = "Invalid or expired invite token"),
+        (status = 404, description = "Invite not found"),
+        (status = 409, description = "Admin already exists"),
+    )
+)]
+pub async fn register_with_invite_handler(
+    State(state): State<AppState>,
+    Json(body): Json<RegisterWithInviteRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+
+    let claims = jwt::decode_invite_token(&body.invite_token)?;
+    let invite_id = id::parse_uuid(&claims.sub).map_err(|_| AppError::InvalidToken)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let invite = sqlx::query!(
+        r#"
+            SELECT org_id, project_id, invitee_email, role, status, expires_at
+            FROM admin_invites
+            WHERE id = $1 AND kind = 'membership'
+            FOR UPDATE
+        "#,
+        invite_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(invite) = invite else {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    };
+
+    // The token is only valid for the invite exactly as it was issued.
+    let bound = invite.invitee_email.as_deref() == Some(claims.email.as_str())
+        && invite.org_id.map(|id| id.to_string()) == claims.org_id
+        && invite.project_id.map(|id| id.to_string()) == claims.project_id
+        && invite.role == claims.role;
+    if !bound {
+        return Err(AppError::InvalidToken);
+    }
+
+    if invite.status != "pending" {
+        return Err(AppError::ValidationError(ValidationErrors::single_error(
+            "Invite is not in pending status".to_string(),
+        )));
+    }
+
+    if invite.expires_at < time::OffsetDateTime::now_utc() {
+        return Err(AppError::ValidationError(ValidationErrors::single_error(
+            "Invite has expired".to_string(),
+        )));
+    }
+
+    let admin_id = id::new_uuid();
+    let password_hash = crypto::hash_password(&body.password)?;
+
+    sqlx::query!(
+        "INSERT INTO admin_users (id, username, password_hash) VALUES ($1, $2, $3)",
+        admin_id,
+        body.username,
+        password_hash,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    add_membership(&mut tx, admin_id, invite.org_id, invite.project_id, &invite.role).await?;
+
+    sqlx::query!(
+        "UPDATE admin_invites SET status = 'accepted', responded_at = NOW(), invitee_username = $2 WHERE id = $1",
+        invite_id,
+        body.username,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "admin_register",
+            success: true,
+            route: "/admin/register/invite",
+            admin_user_id: Some(admin_id),
+            identifier: Some(&claims.email),
+            http_status: Some(201),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let response = RegisterAdminResponse {
+        user_id: admin_id.to_string(),
+        access_token: jwt::generate_admin_token(&admin_id.to_string())?,
+    };
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
 #[utoipa::path(
     post,
     path = "/invites/{invite_id}/decline",
@@ -234,12 +515,12 @@ pub async fn accept_invite_handler(
         (status = 204, description = "Invite declined"),
         (status = 400, description = "Invite not pending"),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Forbidden — caller is not the invitee"),
+        (status = 403, description = "Forbidden — caller is not the invitee, or used a personal access token"),
         (status = 404, description = "Invite not found"),
     )
 )]
 pub async fn decline_invite_handler(
-    AdminId { admin_id }: AdminId,
+    SessionAdmin { admin_id }: SessionAdmin,
     Path(invite_id): Path<String>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -261,7 +542,7 @@ pub async fn decline_invite_handler(
             .fetch_one(&state.pool)
             .await?;
 
-    if invite.invitee_username != caller_username {
+    if invite.invitee_username.as_deref() != Some(caller_username.as_str()) {
         return Err(AppError::Forbidden);
     }
 
@@ -293,12 +574,12 @@ pub async fn decline_invite_handler(
         (status = 204, description = "Invite revoked"),
         (status = 400, description = "Invite not pending"),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Forbidden — caller is not the invite issuer"),
+        (status = 403, description = "Forbidden — caller is not the invite issuer, or used a personal access token"),
         (status = 404, description = "Invite not found"),
     )
 )]
 pub async fn revoke_invite_handler(
-    AdminId { admin_id }: AdminId,
+    SessionAdmin { admin_id }: SessionAdmin,
     Path(invite_id): Path<String>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -334,3 +615,187 @@ pub async fn revoke_invite_handler(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Which invites a listing covers; unset fields do not filter.
+#[derive(Default)]
+struct InviteScope {
+    invitee_username: Option<String>,
+    org_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+}
+
+async fn list_invites(
+    state: &AppState,
+    scope: InviteScope,
+    filter: InviteFilterParams,
+    params: CursorParams,
+) -> Result<CursorPage<InviteListItem>, AppError> {
+    if filter
+        .status
+        .as_deref()
+        .is_some_and(|status| !INVITE_STATUSES.contains(&status))
+    {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "status".to_string(),
+            vec![format!("must be one of: {}", INVITE_STATUSES.join(", "))],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let limit = params.limit();
+    let (cursor_time, cursor_id) = match params.cursor {
+        Some(ref cursor) => {
+            let (time, id) = pagination::decode_cursor(cursor)?;
+            (Some(time), Some(id))
+        }
+        None => (None, None),
+    };
+
+    let items: Vec<InviteListItem> = sqlx::query!(
+        r#"
+            SELECT i.id, i.kind, i.org_id, i.project_id, i.invitee_username, i.invitee_email, i.role,
+                   i.created_at, i.expires_at,
+                   au.username AS inviter_username,
+                   COALESCE(o.name, p.name) AS "scope_name!",
+                   CASE WHEN i.status = 'pending' AND i.expires_at < NOW() THEN 'expired' ELSE i.status END
+                       AS "status!"
+            FROM admin_invites i
+            JOIN admin_users au ON au.id = i.invited_by_admin_user_id
+            LEFT JOIN organizations o ON o.id = i.org_id
+            LEFT JOIN projects p ON p.id = i.project_id
+            WHERE ($1::text IS NULL OR i.invitee_username = $1)
+              AND ($2::uuid IS NULL OR i.org_id = $2)
+              AND ($3::uuid IS NULL OR i.project_id = $3)
+              AND ($4::text IS NULL
+                   OR CASE WHEN i.status = 'pending' AND i.expires_at < NOW() THEN 'expired' ELSE i.status END = $4)
+              AND ($5::timestamptz IS NULL OR (i.created_at, i.id) < ($5, $6))
+            ORDER BY i.created_at DESC, i.id DESC
+            LIMIT $7
+        "#,
+        scope.invitee_username,
+        scope.org_id,
+        scope.project_id,
+        filter.status,
+        cursor_time,
+        cursor_id,
+        limit + 1,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| InviteListItem {
+        id: r.id.to_string(),
+        kind: r.kind,
+        org_id: r.org_id.map(|id| id.to_string()),
+        project_id: r.project_id.map(|id| id.to_string()),
+        scope_name: r.scope_name,
+        invitee_username: r.invitee_username,
+        invitee_email: r.invitee_email,
+        inviter_username: r.inviter_username,
+        role: r.role,
+        status: r.status,
+        created_at: r.created_at,
+        expires_at: r.expires_at,
+    })
+    .collect();
+
+    Ok(CursorPage::from_rows(items, limit, |item| {
+        let id = Uuid::parse_str(&item.id).expect("id from DB is always a valid UUID");
+        pagination::encode_cursor(item.created_at, id)
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/invites/incoming",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(InviteFilterParams, CursorParams),
+    responses(
+        (status = 200, description = "Invites addressed to the caller, newest first", body = CursorPage<InviteListItem>),
+        (status = 400, description = "Invalid status or cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Personal access tokens cannot read the admin's invites"),
+    )
+)]
+pub async fn list_incoming_invites_handler(
+    SessionAdmin { admin_id }: SessionAdmin,
+    State(state): State<AppState>,
+    Query(filter): Query<InviteFilterParams>,
+    Query(params): Query<CursorParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let username = sqlx::query_scalar!("SELECT username FROM admin_users WHERE id = $1", admin_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    let scope = InviteScope {
+        invitee_username: Some(username),
+        ..Default::default()
+    };
+    let page = list_invites(&state, scope, filter, params).await?;
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/invites",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        InviteFilterParams,
+        CursorParams,
+    ),
+    responses(
+        (status = 200, description = "Invites to the organization, newest first", body = CursorPage<InviteListItem>),
+        (status = 400, description = "Invalid status or cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_org_invites_handler(
+    member: RequirePermission<perm::MembersInvite>,
+    State(state): State<AppState>,
+    Query(filter): Query<InviteFilterParams>,
+    Query(params): Query<CursorParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let scope = InviteScope {
+        org_id: Some(member.org_id),
+        ..Default::default()
+    };
+    let page = list_invites(&state, scope, filter, params).await?;
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/invites",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        InviteFilterParams,
+        CursorParams,
+    ),
+    responses(
+        (status = 200, description = "Invites to the project, newest first", body = CursorPage<InviteListItem>),
+        (status = 400, description = "Invalid status or cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_project_invites_handler(
+    member: RequirePermission<perm::MembersInvite, ProjectMember>,
+    State(state): State<AppState>,
+    Query(filter): Query<InviteFilterParams>,
+    Query(params): Query<CursorParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let scope = InviteScope {
+        project_id: Some(member.project_id),
+        ..Default::default()
+    };
+    let page = list_invites(&state, scope, filter, params).await?;
+    Ok((StatusCode::OK, Json(page)))
+}
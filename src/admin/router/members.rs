@@ -0,0 +1,508 @@
+use crate::admin::authorization::{OrgMember, ProjectMember, RequirePermission, Role};
+use crate::admin::policy::{self, Permission, perm};
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, ToSchema)]
+pub struct MemberItem {
+    admin_user_id: String,
+    username: String,
+    /// `owner`, `admin`, or the name of a custom role of the organization.
+    role: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    joined_at: OffsetDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateMemberRequestBody {
+    /// `owner`, `admin`, or the name of a custom role of the organization.
+    role: String,
+}
+
+#[derive(Deserialize)]
+pub struct MemberPath {
+    admin_user_id: String,
+}
+
+/// Roles of a scope's members, read with the rows locked so concurrent changes cannot both
+/// pass the last-owner check.
+struct LockedMembers(Vec<(Uuid, Role)>);
+
+impl LockedMembers {
+    fn role_of(&self, admin_user_id: Uuid) -> Result<Role, AppError> {
+        self.0
+            .iter()
+            .find(|(id, _)| *id == admin_user_id)
+            .map(|(_, role)| *role)
+            .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))
+    }
+
+    /// Fails when `admin_user_id` is the only owner, i.e. when demoting or removing them would
+    /// leave the scope without one.
+    fn ensure_not_last_owner(&self, field: &str, admin_user_id: Uuid) -> Result<(), AppError> {
+        let owners = self.0.iter().filter(|(_, role)| *role == Role::Owner).count();
+        if self.role_of(admin_user_id)? == Role::Owner && owners == 1 {
+            let mut errors = HashMap::new();
+            errors.insert(
+                field.to_string(),
+                vec!["the last owner cannot be demoted or removed; make another member owner first".to_string()],
+            );
+            return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+        }
+        Ok(())
+    }
+}
+
+/// A built-in role, or a custom role of the org, which members hold on top of `admin`.
+pub(super) struct RoleAssignment {
+    pub(super) role: Role,
+    pub(super) custom_role_id: Option<Uuid>,
+    /// What a membership with this assignment may do in the org.
+    pub(super) permissions: Vec<Permission>,
+}
+
+pub(super) async fn resolve_role(state: &AppState, org_id: Uuid, name: &str) -> Result<RoleAssignment, AppError> {
+    if let Some(role) = Role::parse(name) {
+        return Ok(RoleAssignment {
+            role,
+            custom_role_id: None,
+            permissions: policy::granted(role, None),
+        });
+    }
+
+    let custom_role = sqlx::query!(
+        "SELECT id, permissions FROM admin_org_roles WHERE org_id = $1 AND name = $2",
+        org_id,
+        name,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    match custom_role {
+        Some(custom_role) => Ok(RoleAssignment {
+            role: Role::Admin,
+            custom_role_id: Some(custom_role.id),
+            permissions: policy::granted(Role::Admin, Some(&custom_role.permissions)),
+        }),
+        None => {
+            let mut errors = HashMap::new();
+            errors.insert(
+                "role".to_string(),
+                vec!["must be 'owner', 'admin' or a custom role of the organization".to_string()],
+            );
+            Err(AppError::ValidationError(ValidationErrors::new(errors)))
+        }
+    }
+}
+
+async fn lock_org_members(tx: &mut Transaction<'_, Postgres>, org_id: Uuid) -> Result<LockedMembers, AppError> {
+    let rows = sqlx::query!(
+        "SELECT admin_user_id, role FROM admin_org_memberships WHERE org_id = $1 FOR UPDATE",
+        org_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(LockedMembers(
+        rows.into_iter()
+            .filter_map(|r| Role::parse(&r.role).map(|role| (r.admin_user_id, role)))
+            .collect(),
+    ))
+}
+
+async fn lock_project_members(tx: &mut Transaction<'_, Postgres>, project_id: Uuid) -> Result<LockedMembers, AppError> {
+    let rows = sqlx::query!(
+        "SELECT admin_user_id, role FROM admin_project_memberships WHERE project_id = $1 FOR UPDATE",
+        project_id,
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(LockedMembers(
+        rows.into_iter()
+            .filter_map(|r| Role::parse(&r.role).map(|role| (r.admin_user_id, role)))
+            .collect(),
+    ))
+}
+
+async fn remove_org_member(state: &AppState, org_id: Uuid, admin_user_id: Uuid, field: &str) -> Result<(), AppError> {
+    let mut tx = state.pool.begin().await?;
+    let members = lock_org_members(&mut tx, org_id).await?;
+    members.ensure_not_last_owner(field, admin_user_id)?;
+
+    sqlx::query!(
+        "DELETE FROM admin_org_memberships WHERE org_id = $1 AND admin_user_id = $2",
+        org_id,
+        admin_user_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn remove_project_member(
+    state: &AppState,
+    project_id: Uuid,
+    admin_user_id: Uuid,
+    field: &str,
+) -> Result<(), AppError> {
+    let mut tx = state.pool.begin().await?;
+    let members = lock_project_members(&mut tx, project_id).await?;
+    members.ensure_not_last_owner(field, admin_user_id)?;
+
+    sqlx::query!(
+        "DELETE FROM admin_project_memberships WHERE project_id = $1 AND admin_user_id = $2",
+        project_id,
+        admin_user_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// ─── Org members ─────────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/members",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 200, description = "Members of the organization, oldest first", body = Vec<MemberItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Organization not found"),
+    )
+)]
+pub async fn list_org_members_handler(
+    member: RequirePermission<perm::OrgRead>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let members: Vec<MemberItem> = sqlx::query!(
+        r#"
+            SELECT m.admin_user_id, au.username, COALESCE(r.name, m.role) AS "role!", m.created_at
+            FROM admin_org_memberships m
+            JOIN admin_users au ON au.id = m.admin_user_id
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.org_id = $1
+            ORDER BY m.created_at, m.id
+        "#,
+        member.org_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| MemberItem {
+        admin_user_id: r.admin_user_id.to_string(),
+        username: r.username,
+        role: r.role,
+        joined_at: r.created_at,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(members)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}/members/{admin_user_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("admin_user_id" = String, Path, description = "Admin user ID (UUID v7)"),
+    ),
+    request_body = UpdateMemberRequestBody,
+    responses(
+        (status = 204, description = "Role changed"),
+        (status = 400, description = "Unknown role, or the member is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks members.role.update"),
+        (status = 404, description = "Member not found in this organization"),
+    )
+)]
+pub async fn update_org_member_handler(
+    member: RequirePermission<perm::MembersRoleUpdate>,
+    Path(MemberPath { admin_user_id }): Path<MemberPath>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdateMemberRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let admin_user_id = id::parse_uuid(&admin_user_id)?;
+    let assignment = resolve_role(&state, member.org_id, &body.role).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let members = lock_org_members(&mut tx, member.org_id).await?;
+    members.role_of(admin_user_id)?;
+    if assignment.role != Role::Owner {
+        members.ensure_not_last_owner("role", admin_user_id)?;
+    }
+
+    sqlx::query!(
+        r#"
+            UPDATE admin_org_memberships SET role = $3, custom_role_id = $4
+            WHERE org_id = $1 AND admin_user_id = $2
+        "#,
+        member.org_id,
+        admin_user_id,
+        assignment.role.as_str(),
+        assignment.custom_role_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/members/{admin_user_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("admin_user_id" = String, Path, description = "Admin user ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Member removed; their project memberships are kept"),
+        (status = 400, description = "The member is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks members.remove, or the member is an owner and the caller is not"),
+        (status = 404, description = "Member not found in this organization"),
+    )
+)]
+pub async fn remove_org_member_handler(
+    member: RequirePermission<perm::MembersRemove>,
+    Path(MemberPath { admin_user_id }): Path<MemberPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let admin_user_id = id::parse_uuid(&admin_user_id)?;
+
+    if member.role != Some(Role::Owner) {
+        let target_role = sqlx::query_scalar!(
+            "SELECT role FROM admin_org_memberships WHERE org_id = $1 AND admin_user_id = $2",
+            member.org_id,
+            admin_user_id,
+        )
+        .fetch_one(&state.pool)
+        .await?;
+        if target_role == Role::Owner.as_str() {
+            return Err(AppError::Forbidden);
+        }
+    }
+
+    remove_org_member(&state, member.org_id, admin_user_id, "admin_user_id").await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/leave",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 204, description = "Caller left the organization"),
+        (status = 400, description = "The caller is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not a member"),
+        (status = 404, description = "Organization not found"),
+    )
+)]
+pub async fn leave_org_handler(
+    member: OrgMember,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    if member.role.is_none() {
+        return Err(AppError::Forbidden);
+    }
+    remove_org_member(&state, member.org_id, member.admin_id, "org_id").await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ─── Project members ─────────────────────────────────────────────────────────
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/members",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Direct members of the project, oldest first; org members have access without being listed", body = Vec<MemberItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project not found"),
+    )
+)]
+pub async fn list_project_members_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let members: Vec<MemberItem> = sqlx::query!(
+        r#"
+            SELECT m.admin_user_id, au.username, COALESCE(r.name, m.role) AS "role!", m.created_at
+            FROM admin_project_memberships m
+            JOIN admin_users au ON au.id = m.admin_user_id
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.project_id = $1
+            ORDER BY m.created_at, m.id
+        "#,
+        member.project_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| MemberItem {
+        admin_user_id: r.admin_user_id.to_string(),
+        username: r.username,
+        role: r.role,
+        joined_at: r.created_at,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(members)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}/projects/{project_id}/members/{admin_user_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("admin_user_id" = String, Path, description = "Admin user ID (UUID v7)"),
+    ),
+    request_body = UpdateMemberRequestBody,
+    responses(
+        (status = 204, description = "Role changed"),
+        (status = 400, description = "Unknown role, or the member is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks members.role.update"),
+        (status = 404, description = "Member not found in this project"),
+    )
+)]
+pub async fn update_project_member_handler(
+    member: RequirePermission<perm::MembersRoleUpdate, ProjectMember>,
+    Path(MemberPath { admin_user_id }): Path<MemberPath>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdateMemberRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let admin_user_id = id::parse_uuid(&admin_user_id)?;
+    let project_id = member.project_id;
+    let assignment = resolve_role(&state, member.org_id, &body.role).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let members = lock_project_members(&mut tx, project_id).await?;
+    members.role_of(admin_user_id)?;
+    if assignment.role != Role::Owner {
+        members.ensure_not_last_owner("role", admin_user_id)?;
+    }
+
+    sqlx::query!(
+        r#"
+            UPDATE admin_project_memberships SET role = $3, custom_role_id = $4
+            WHERE project_id = $1 AND admin_user_id = $2
+        "#,
+        project_id,
+        admin_user_id,
+        assignment.role.as_str(),
+        assignment.custom_role_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/members/{admin_user_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("admin_user_id" = String, Path, description = "Admin user ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 400, description = "The member is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks members.remove, or the member is an owner and the caller is not"),
+        (status = 404, description = "Member not found in this project"),
+    )
+)]
+pub async fn remove_project_member_handler(
+    member: RequirePermission<perm::MembersRemove, ProjectMember>,
+    Path(MemberPath { admin_user_id }): Path<MemberPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let admin_user_id = id::parse_uuid(&admin_user_id)?;
+    let project_id = member.project_id;
+
+    if member.role != Role::Owner {
+        let target_role = sqlx::query_scalar!(
+            "SELECT role FROM admin_project_memberships WHERE project_id = $1 AND admin_user_id = $2",
+            project_id,
+            admin_user_id,
+        )
+        .fetch_one(&state.pool)
+        .await?;
+        if target_role == Role::Owner.as_str() {
+            return Err(AppError::Forbidden);
+        }
+    }
+
+    remove_project_member(&state, project_id, admin_user_id, "admin_user_id").await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/leave",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Caller left the project"),
+        (status = 400, description = "The caller is the last owner"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not a member"),
+        (status = 404, description = "Project not found, or the caller only has access through the organization"),
+    )
+)]
+pub async fn leave_project_handler(
+    member: ProjectMember,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    remove_project_member(&state, member.project_id, member.admin_id, "project_id").await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,157 @@
+use super::OrgResponse;
+use crate::admin::authorization::{AnyOrgMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::admin::retention;
+use crate::error::{AppError, ValidationErrors};
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateOrgRequestBody {
+    name: String,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    request_body = UpdateOrgRequestBody,
+    responses(
+        (status = 200, description = "Organization renamed", body = OrgResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Organization not found"),
+        (status = 409, description = "Another organization already has this name"),
+    )
+)]
+pub async fn update_org_handler(
+    member: RequirePermission<perm::OrgUpdate>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdateOrgRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    if body.name.trim().is_empty() {
+        let mut errors = HashMap::new();
+        errors.insert("name".to_string(), vec!["must not be empty".to_string()]);
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let record = sqlx::query!(
+        "UPDATE organizations SET name = $2 WHERE id = $1 AND deleted_at IS NULL RETURNING id, name",
+        member.org_id,
+        body.name,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrgResponse {
+            id: record.id.to_string(),
+            name: record.name,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 204, description = "Organization hidden along with its projects and applications; it can be restored within the restore window"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Organization not found"),
+    )
+)]
+pub async fn delete_org_handler(
+    member: RequirePermission<perm::OrgDelete>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    // Projects and applications keep their own `deleted_at`; lookups check the org too, so
+    // restoring the org brings back exactly what was live before.
+    sqlx::query!(
+        "UPDATE organizations SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        member.org_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/restore",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 200, description = "Organization restored", body = OrgResponse),
+        (status = 400, description = "The restore window has passed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Organization is not deleted"),
+        (status = 409, description = "Another organization took the name in the meantime"),
+    )
+)]
+pub async fn restore_org_handler(
+    member: RequirePermission<perm::OrgDelete, AnyOrgMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let deleted_at = member.deleted_at.ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+    retention::ensure_restorable("org_id", deleted_at)?;
+
+    let record = sqlx::query!(
+        "UPDATE organizations SET deleted_at = NULL WHERE id = $1 RETURNING id, name",
+        member.org_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(OrgResponse {
+            id: record.id.to_string(),
+            name: record.name,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/purge",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 204, description = "Organization erased with everything in it; auth events are kept without the link"),
+        (status = 400, description = "The organization can still be restored"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Only owners can purge"),
+        (status = 404, description = "Organization is not deleted"),
+    )
+)]
+pub async fn purge_org_handler(
+    member: RequirePermission<perm::OrgPurge, AnyOrgMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let deleted_at = member.deleted_at.ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+    retention::ensure_purgeable("org_id", deleted_at)?;
+
+    sqlx::query!("DELETE FROM organizations WHERE id = $1", member.org_id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,275 @@
+use super::invites::{InviteResponse, invite_expires_at};
+use crate::admin::authorization::{ProjectMember, RequirePermission, Role};
+use crate::admin::policy::perm;
+use crate::audit::{self, AuthEvent};
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use sqlx::{Postgres, Transaction};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Deserialize, ToSchema)]
+pub struct OwnershipTransferRequestBody {
+    /// Username of an existing admin member who becomes owner on accepting.
+    to_username: String,
+}
+
+fn transfer_error(message: &str) -> AppError {
+    AppError::ValidationError(ValidationErrors::single_error(message.to_string()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/ownership-transfers",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    request_body = OwnershipTransferRequestBody,
+    responses(
+        (status = 201, description = "Transfer proposed; the target accepts it like an invite", body = InviteResponse),
+        (status = 400, description = "Target is not an admin member of the organization"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Only owners can transfer ownership"),
+        (status = 409, description = "A transfer is already pending for this organization"),
+    )
+)]
+pub async fn propose_org_transfer_handler(
+    member: RequirePermission<perm::OwnershipTransfer>,
+    State(state): State<AppState>,
+    Json(body): Json<OwnershipTransferRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let target_role = sqlx::query_scalar!(
+        r#"
+            SELECT m.role
+            FROM admin_org_memberships m
+            JOIN admin_users au ON au.id = m.admin_user_id
+            WHERE m.org_id = $1 AND au.username = $2
+        "#,
+        member.org_id,
+        body.to_username,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    if target_role.as_deref() != Some(Role::Admin.as_str()) {
+        return Err(transfer_error("Ownership can only be transferred to an admin member"));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // An expired transfer would otherwise block new ones until it is cleaned up.
+    sqlx::query!(
+        r#"
+            UPDATE admin_invites SET status = 'expired'
+            WHERE org_id = $1 AND kind = 'ownership_transfer' AND status = 'pending' AND expires_at < NOW()
+        "#,
+        member.org_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let invite_id = id::new_uuid();
+    sqlx::query!(
+        r#"
+            INSERT INTO admin_invites
+                (id, invited_by_admin_user_id, org_id, invitee_username, role, status, expires_at, kind)
+            VALUES ($1, $2, $3, $4, 'owner', 'pending', $5, 'ownership_transfer')
+        "#,
+        invite_id,
+        member.admin_id,
+        member.org_id,
+        body.to_username,
+        invite_expires_at(),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InviteResponse {
+            id: invite_id.to_string(),
+            invite_token: None,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/ownership-transfers",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    request_body = OwnershipTransferRequestBody,
+    responses(
+        (status = 201, description = "Transfer proposed; the target accepts it like an invite", body = InviteResponse),
+        (status = 400, description = "Target is not an admin member of the project"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Only direct project owners can transfer ownership"),
+        (status = 409, description = "A transfer is already pending for this project"),
+    )
+)]
+pub async fn propose_project_transfer_handler(
+    member: RequirePermission<perm::OwnershipTransfer, ProjectMember>,
+    State(state): State<AppState>,
+    Json(body): Json<OwnershipTransferRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    // Org members reach every project without a project membership, so they have no project
+    // ownership to hand over.
+    let caller_role = sqlx::query_scalar!(
+        "SELECT role FROM admin_project_memberships WHERE project_id = $1 AND admin_user_id = $2",
+        member.project_id,
+        member.admin_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    if caller_role.as_deref() != Some(Role::Owner.as_str()) {
+        return Err(AppError::Forbidden);
+    }
+
+    let target_role = sqlx::query_scalar!(
+        r#"
+            SELECT m.role
+            FROM admin_project_memberships m
+            JOIN admin_users au ON au.id = m.admin_user_id
+            WHERE m.project_id = $1 AND au.username = $2
+        "#,
+        member.project_id,
+        body.to_username,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    if target_role.as_deref() != Some(Role::Admin.as_str()) {
+        return Err(transfer_error("Ownership can only be transferred to an admin member"));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+            UPDATE admin_invites SET status = 'expired'
+            WHERE project_id = $1 AND kind = 'ownership_transfer' AND status = 'pending' AND expires_at < NOW()
+        "#,
+        member.project_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let invite_id = id::new_uuid();
+    sqlx::query!(
+        r#"
+            INSERT INTO admin_invites
+                (id, invited_by_admin_user_id, project_id, invitee_username, role, status, expires_at, kind)
+            VALUES ($1, $2, $3, $4, 'owner', 'pending', $5, 'ownership_transfer')
+        "#,
+        invite_id,
+        member.admin_id,
+        member.project_id,
+        body.to_username,
+        invite_expires_at(),
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InviteResponse {
+            id: invite_id.to_string(),
+            invite_token: None,
+        }),
+    ))
+}
+
+/// Promotes the accepting admin and demotes the issuer in the caller's transaction, then
+/// records the handover. Fails if either side's membership changed since the proposal.
+pub(super) async fn complete_transfer(
+    tx: &mut Transaction<'_, Postgres>,
+    invite_id: Uuid,
+    from_admin_id: Uuid,
+    to_admin_id: Uuid,
+    org_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    let (promoted, demoted) = if let Some(org_id) = org_id {
+        let promoted = sqlx::query!(
+            "UPDATE admin_org_memberships SET role = 'owner' WHERE org_id = $1 AND admin_user_id = $2",
+            org_id,
+            to_admin_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        let demoted = sqlx::query!(
+            r#"
+                UPDATE admin_org_memberships SET role = 'admin'
+                WHERE org_id = $1 AND admin_user_id = $2 AND role = 'owner'
+            "#,
+            org_id,
+            from_admin_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        (promoted.rows_affected(), demoted.rows_affected())
+    } else if let Some(project_id) = project_id {
+        let promoted = sqlx::query!(
+            "UPDATE admin_project_memberships SET role = 'owner' WHERE project_id = $1 AND admin_user_id = $2",
+            project_id,
+            to_admin_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        let demoted = sqlx::query!(
+            r#"
+                UPDATE admin_project_memberships SET role = 'admin'
+                WHERE project_id = $1 AND admin_user_id = $2 AND role = 'owner'
+            "#,
+            project_id,
+            from_admin_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+        (promoted.rows_affected(), demoted.rows_affected())
+    } else {
+        (0, 0)
+    };
+
+    if promoted != 1 {
+        return Err(transfer_error("You are no longer a member"));
+    }
+    if demoted != 1 {
+        return Err(transfer_error("The issuer is no longer an owner"));
+    }
+
+    let from_username = sqlx::query_scalar!("SELECT username FROM admin_users WHERE id = $1", from_admin_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    audit::write_auth_event(
+        &mut **tx,
+        AuthEvent {
+            event_type: "ownership_transfer",
+            success: true,
+            route: &format!("/admin/invites/{invite_id}/accept"),
+            admin_user_id: Some(to_admin_id),
+            identifier: Some(&from_username),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok(())
+}
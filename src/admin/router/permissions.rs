@@ -0,0 +1,314 @@
+use super::AppIdPath;
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Serialize, ToSchema)]
+pub struct PermissionItem {
+    id: String,
+    name: String,
+    description: Option<String>,
+    /// Accounts holding the permission directly through `account_scopes`.
+    account_grants: i64,
+    /// Project roles bundling the permission.
+    role_grants: i64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdatePermissionRequestBody {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeletePermissionParams {
+    /// Required when the permission is still granted to accounts or bundled by roles.
+    #[serde(default)]
+    confirm: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeletePermissionResponse {
+    removed_account_scopes: i64,
+    removed_role_permissions: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PermissionPath {
+    app_id: String,
+    permission_id: String,
+}
+
+/// Errors for a permission name, empty when it is valid. Names end up in the space-delimited
+/// token `scope` claim, so they cannot contain whitespace.
+pub(super) fn name_errors(name: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if name.is_empty() {
+        errors.push("must not be empty".to_string());
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        errors.push(format!("must be at most {MAX_NAME_LENGTH} characters"));
+    }
+    if name.chars().any(char::is_whitespace) {
+        errors.push("must not contain whitespace".to_string());
+    }
+    errors
+}
+
+/// Makes sure the application belongs to the member's project.
+pub(super) async fn application_in_project(
+    state: &AppState,
+    member: &ProjectMember,
+    app_id: Uuid,
+) -> Result<(), AppError> {
+    let in_project: bool = sqlx::query_scalar!(
+        "SELECT EXISTS(SELECT 1 FROM applications WHERE id = $1 AND project_id = $2 AND deleted_at IS NULL)",
+        app_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?
+    .unwrap_or(false);
+
+    if !in_project {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    Ok(())
+}
+
+async fn load_permission(state: &AppState, app_id: Uuid, permission_id: Uuid) -> Result<PermissionItem, AppError> {
+    let record = sqlx::query!(
+        r#"
+            SELECT p.id, p.name, p.description,
+                   (SELECT COUNT(*) FROM account_scopes s WHERE s.permission_id = p.id) AS "account_grants!",
+                   (SELECT COUNT(*) FROM project_role_permissions rp WHERE rp.permission_id = p.id) AS "role_grants!"
+            FROM permissions p
+            WHERE p.id = $1 AND p.app_id = $2
+        "#,
+        permission_id,
+        app_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(PermissionItem {
+        id: record.id.to_string(),
+        name: record.name,
+        description: record.description,
+        account_grants: record.account_grants,
+        role_grants: record.role_grants,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/scopes",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Permissions defined on the application", body = Vec<PermissionItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+    )
+)]
+pub async fn list_permissions_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    Path(AppIdPath { app_id }): Path<AppIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    application_in_project(&state, &member, app_id).await?;
+
+    let permissions: Vec<PermissionItem> = sqlx::query!(
+        r#"
+            SELECT p.id, p.name, p.description,
+                   (SELECT COUNT(*) FROM account_scopes s WHERE s.permission_id = p.id) AS "account_grants!",
+                   (SELECT COUNT(*) FROM project_role_permissions rp WHERE rp.permission_id = p.id) AS "role_grants!"
+            FROM permissions p
+            WHERE p.app_id = $1
+            ORDER BY p.name
+        "#,
+        app_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| PermissionItem {
+        id: r.id.to_string(),
+        name: r.name,
+        description: r.description,
+        account_grants: r.account_grants,
+        role_grants: r.role_grants,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(permissions)))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/scopes/{permission_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+        ("permission_id" = String, Path, description = "Permission ID (UUID v7)"),
+    ),
+    request_body = UpdatePermissionRequestBody,
+    responses(
+        (status = 200, description = "Permission renamed or redescribed; grants are kept", body = PermissionItem),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application or permission not found in this project"),
+        (status = 409, description = "The application already has a permission with this name"),
+    )
+)]
+pub async fn update_permission_handler(
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
+    Path(PermissionPath { app_id, permission_id }): Path<PermissionPath>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdatePermissionRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    let permission_id = id::parse_uuid(&permission_id)?;
+
+    let mut errors = HashMap::new();
+    if body.name.is_none() && body.description.is_none() {
+        errors.insert("name".to_string(), vec!["name or description is required".to_string()]);
+    }
+    if let Some(name) = &body.name {
+        let name_errors = name_errors(name);
+        if !name_errors.is_empty() {
+            errors.insert("name".to_string(), name_errors);
+        }
+    }
+    if body.description.as_deref().is_some_and(str::is_empty) {
+        errors.insert("description".to_string(), vec!["must not be empty".to_string()]);
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    application_in_project(&state, &member, app_id).await?;
+
+    let updated = sqlx::query!(
+        r#"
+            UPDATE permissions
+            SET name = COALESCE($3, name), description = COALESCE($4, description)
+            WHERE id = $1 AND app_id = $2
+        "#,
+        permission_id,
+        app_id,
+        body.name,
+        body.description,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    let permission = load_permission(&state, app_id, permission_id).await?;
+    Ok((StatusCode::OK, Json(permission)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/applications/{app_id}/scopes/{permission_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("app_id" = String, Path, description = "Application ID (UUID v7)"),
+        ("permission_id" = String, Path, description = "Permission ID (UUID v7)"),
+        DeletePermissionParams,
+    ),
+    responses(
+        (status = 200, description = "Permission deleted along with its grants", body = DeletePermissionResponse),
+        (status = 400, description = "Permission is still granted and `confirm` was not set"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application or permission not found in this project"),
+    )
+)]
+pub async fn delete_permission_handler(
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
+    Path(PermissionPath { app_id, permission_id }): Path<PermissionPath>,
+    Query(params): Query<DeletePermissionParams>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let app_id = id::parse_uuid(&app_id)?;
+    let permission_id = id::parse_uuid(&permission_id)?;
+    application_in_project(&state, &member, app_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Lock the permission so grants cannot be added between counting and deleting.
+    sqlx::query!(
+        "SELECT id FROM permissions WHERE id = $1 AND app_id = $2 FOR UPDATE",
+        permission_id,
+        app_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let counts = sqlx::query!(
+        r#"
+            SELECT (SELECT COUNT(*) FROM account_scopes WHERE permission_id = $1) AS "account_scopes!",
+                   (SELECT COUNT(*) FROM project_role_permissions WHERE permission_id = $1) AS "role_permissions!"
+        "#,
+        permission_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if (counts.account_scopes > 0 || counts.role_permissions > 0) && !params.confirm {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "confirm".to_string(),
+            vec![format!(
+                "permission is granted to {} account(s) and {} role(s); pass confirm=true to delete it",
+                counts.account_scopes, counts.role_permissions
+            )],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    // Grants go with the permission through ON DELETE CASCADE.
+    sqlx::query!("DELETE FROM permissions WHERE id = $1", permission_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(DeletePermissionResponse {
+            removed_account_scopes: counts.account_scopes,
+            removed_role_permissions: counts.role_permissions,
+        }),
+    ))
+}
@@ -0,0 +1,303 @@
+use crate::admin::authorization::{AdminId, SessionAdmin};
+use crate::admin::policy::Permission;
+use crate::admin::tokens;
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// The longest a personal access token may live.
+const MAX_LIFETIME: Duration = Duration::days(365);
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateTokenRequestBody {
+    #[validate(length(min = 1, max = 100, message = "Should have from 1 to 100 characters"))]
+    name: String,
+    /// Organizations the token may act on; the caller must belong to each.
+    org_ids: Vec<String>,
+    /// Catalog permission names, e.g. `app.secret.rotate`. Requests get the ones the caller also holds.
+    permissions: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateTokenResponse {
+    id: String,
+    name: String,
+    /// Shown once; only its hash is stored. Send it as `Authorization: Bearer <token>`.
+    token: String,
+    org_ids: Vec<String>,
+    permissions: Vec<Permission>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TokenItem {
+    id: String,
+    name: String,
+    org_ids: Vec<String>,
+    permissions: Vec<Permission>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_used_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct TokenPath {
+    token_id: String,
+}
+
+/// Checks what `validate` cannot and returns the org IDs and canonical permission names.
+async fn token_scope(
+    state: &AppState,
+    admin_id: Uuid,
+    body: &CreateTokenRequestBody,
+) -> Result<(Vec<Uuid>, Vec<String>), AppError> {
+    let mut errors = HashMap::new();
+
+    let now = OffsetDateTime::now_utc();
+    if body.expires_at <= now {
+        errors.insert("expires_at".to_string(), vec!["must be in the future".to_string()]);
+    } else if body.expires_at > now + MAX_LIFETIME {
+        errors.insert(
+            "expires_at".to_string(),
+            vec![format!("must be within {} days", MAX_LIFETIME.whole_days())],
+        );
+    }
+
+    if body.org_ids.is_empty() {
+        errors.insert("org_ids".to_string(), vec!["must not be empty".to_string()]);
+    }
+    let mut org_ids = Vec::new();
+    for (i, org_id) in body.org_ids.iter().enumerate() {
+        match id::parse_uuid(org_id) {
+            Ok(org_id) if !org_ids.contains(&org_id) => org_ids.push(org_id),
+            Ok(_) => {}
+            Err(_) => {
+                errors.insert(format!("org_ids[{i}]"), vec!["is not a valid ID".to_string()]);
+            }
+        }
+    }
+
+    if body.permissions.is_empty() {
+        errors.insert("permissions".to_string(), vec!["must not be empty".to_string()]);
+    }
+    let mut permissions = Vec::new();
+    for (i, name) in body.permissions.iter().enumerate() {
+        match Permission::parse(name) {
+            Some(permission) => {
+                if !permissions.contains(&permission.as_str().to_string()) {
+                    permissions.push(permission.as_str().to_string());
+                }
+            }
+            None => {
+                errors.insert(
+                    format!("permissions[{i}]"),
+                    vec!["is not in the permission catalog".to_string()],
+                );
+            }
+        }
+    }
+
+    // Members of only some of an org's projects belong to it too.
+    let reachable = sqlx::query_scalar!(
+        r#"
+            SELECT o.id
+            FROM organizations o
+            WHERE o.id = ANY($2) AND o.deleted_at IS NULL
+              AND (
+                  EXISTS (SELECT 1 FROM admin_org_memberships m WHERE m.org_id = o.id AND m.admin_user_id = $1)
+                  OR EXISTS (
+                      SELECT 1
+                      FROM admin_project_memberships m
+                      JOIN projects p ON p.id = m.project_id
+                      WHERE p.org_id = o.id AND m.admin_user_id = $1
+                  )
+              )
+        "#,
+        admin_id,
+        &org_ids,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    for (i, org_id) in body.org_ids.iter().enumerate() {
+        if let Ok(org_id) = id::parse_uuid(org_id)
+            && !reachable.contains(&org_id)
+        {
+            errors.insert(
+                format!("org_ids[{i}]"),
+                vec!["is not an organization you belong to".to_string()],
+            );
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+    Ok((org_ids, permissions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/tokens",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    request_body = CreateTokenRequestBody,
+    responses(
+        (status = 201, description = "Token created; the raw token is only in this response", body = CreateTokenResponse),
+        (status = 400, description = "Validation error, unknown permission or an org the caller does not belong to"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Personal access tokens cannot create tokens"),
+    )
+)]
+pub async fn create_token_handler(
+    admin: SessionAdmin,
+    State(state): State<AppState>,
+    Json(body): Json<CreateTokenRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let (org_ids, permissions) = token_scope(&state, admin.admin_id, &body).await?;
+
+    let token_id = id::new_uuid();
+    let (token, token_hash) = tokens::generate(token_id)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO admin_personal_access_tokens (id, admin_user_id, name, token_hash, permissions, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        token_id,
+        admin.admin_id,
+        body.name,
+        token_hash,
+        &permissions,
+        body.expires_at,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO admin_personal_access_token_orgs (token_id, org_id) SELECT $1, UNNEST($2::uuid[])",
+        token_id,
+        &org_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateTokenResponse {
+            id: token_id.to_string(),
+            name: body.name,
+            token,
+            org_ids: org_ids.iter().map(Uuid::to_string).collect(),
+            permissions: permissions.iter().filter_map(|name| Permission::parse(name)).collect(),
+            expires_at: body.expires_at,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/tokens",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "The caller's personal access tokens, newest first, including revoked and expired ones", body = Vec<TokenItem>),
+        (status = 401, description = "Unauthorized"),
+    )
+)]
+pub async fn list_tokens_handler(admin: AdminId, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let tokens: Vec<TokenItem> = sqlx::query!(
+        r#"
+            SELECT t.id, t.name, t.permissions, t.expires_at, t.last_used_at, t.revoked_at, t.created_at,
+                   ARRAY(
+                       SELECT o.org_id FROM admin_personal_access_token_orgs o WHERE o.token_id = t.id ORDER BY o.org_id
+                   ) AS "org_ids!"
+            FROM admin_personal_access_tokens t
+            WHERE t.admin_user_id = $1
+            ORDER BY t.created_at DESC, t.id DESC
+        "#,
+        admin.admin_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| TokenItem {
+        id: r.id.to_string(),
+        name: r.name,
+        org_ids: r.org_ids.iter().map(Uuid::to_string).collect(),
+        permissions: r
+            .permissions
+            .iter()
+            .filter_map(|name| Permission::parse(name))
+            .collect(),
+        expires_at: r.expires_at,
+        last_used_at: r.last_used_at,
+        revoked_at: r.revoked_at,
+        created_at: r.created_at,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(tokens)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/tokens/{token_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("token_id" = String, Path, description = "Token ID (UUID v7)")),
+    responses(
+        (status = 204, description = "Token revoked; it stops working immediately"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No active token with this ID belongs to the caller"),
+    )
+)]
+pub async fn revoke_token_handler(
+    admin: AdminId,
+    Path(TokenPath { token_id }): Path<TokenPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let token_id = id::parse_uuid(&token_id)?;
+
+    sqlx::query!(
+        r#"
+            UPDATE admin_personal_access_tokens SET revoked_at = NOW()
+            WHERE id = $1 AND admin_user_id = $2 AND revoked_at IS NULL
+            RETURNING id
+        "#,
+        token_id,
+        admin.admin_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
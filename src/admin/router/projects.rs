@@ -0,0 +1,209 @@
+use super::ProjectResponse;
+use crate::admin::authorization::{AnyProjectMember, ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::admin::retention;
+use crate::error::{AppError, ValidationErrors};
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateProjectRequestBody {
+    name: Option<String>,
+    shared_identity_context: Option<bool>,
+    /// Whether admins may act as the project's end users. Defaults to `true` for new projects.
+    allow_impersonation: Option<bool>,
+    /// Whether partners may register applications with an initial access token. Defaults to `false`.
+    allow_dynamic_registration: Option<bool>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/orgs/{org_id}/projects/{project_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    request_body = UpdateProjectRequestBody,
+    responses(
+        (status = 200, description = "Project updated", body = ProjectResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project not found"),
+        (status = 409, description = "Another project of the organization already has this name"),
+    )
+)]
+pub async fn update_project_handler(
+    member: RequirePermission<perm::ProjectUpdate, ProjectMember>,
+    State(state): State<AppState>,
+    Json(body): Json<UpdateProjectRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut errors = HashMap::new();
+    if body.name.is_none()
+        && body.shared_identity_context.is_none()
+        && body.allow_impersonation.is_none()
+        && body.allow_dynamic_registration.is_none()
+    {
+        errors.insert(
+            "name".to_string(),
+            vec![
+                "name, shared_identity_context, allow_impersonation or allow_dynamic_registration is required"
+                    .to_string(),
+            ],
+        );
+    }
+    if body.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+        errors.insert("name".to_string(), vec!["must not be empty".to_string()]);
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let record = sqlx::query!(
+        r#"
+            UPDATE projects
+            SET name = COALESCE($2, name), shared_identity_context = COALESCE($3, shared_identity_context),
+                allow_impersonation = COALESCE($4, allow_impersonation),
+                allow_dynamic_registration = COALESCE($5, allow_dynamic_registration)
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, org_id, name, shared_identity_context, allow_impersonation, allow_dynamic_registration
+        "#,
+        member.project_id,
+        body.name,
+        body.shared_identity_context,
+        body.allow_impersonation,
+        body.allow_dynamic_registration,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProjectResponse {
+            id: record.id.to_string(),
+            org_id: record.org_id.to_string(),
+            name: record.name,
+            shared_identity_context: record.shared_identity_context,
+            allow_impersonation: record.allow_impersonation,
+            allow_dynamic_registration: record.allow_dynamic_registration,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Project hidden along with its applications; it can be restored within the restore window"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project not found"),
+    )
+)]
+pub async fn delete_project_handler(
+    member: RequirePermission<perm::ProjectDelete, ProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    sqlx::query!(
+        "UPDATE projects SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        member.project_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/restore",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Project restored", body = ProjectResponse),
+        (status = 400, description = "The restore window has passed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Project is not deleted, or its organization is"),
+        (status = 409, description = "Another project of the organization took the name in the meantime"),
+    )
+)]
+pub async fn restore_project_handler(
+    member: RequirePermission<perm::ProjectDelete, AnyProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let deleted_at = member.deleted_at.ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+    retention::ensure_restorable("project_id", deleted_at)?;
+
+    let record = sqlx::query!(
+        r#"
+            UPDATE projects SET deleted_at = NULL
+            WHERE id = $1
+            RETURNING id, org_id, name, shared_identity_context, allow_impersonation, allow_dynamic_registration
+        "#,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ProjectResponse {
+            id: record.id.to_string(),
+            org_id: record.org_id.to_string(),
+            name: record.name,
+            shared_identity_context: record.shared_identity_context,
+            allow_impersonation: record.allow_impersonation,
+            allow_dynamic_registration: record.allow_dynamic_registration,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/purge",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Project erased with its applications and accounts; auth events are kept without the link"),
+        (status = 400, description = "The project can still be restored"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Only owners can purge"),
+        (status = 404, description = "Project is not deleted"),
+    )
+)]
+pub async fn purge_project_handler(
+    member: RequirePermission<perm::ProjectPurge, AnyProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let deleted_at = member.deleted_at.ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+    retention::ensure_purgeable("project_id", deleted_at)?;
+
+    sqlx::query!("DELETE FROM projects WHERE id = $1", member.project_id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
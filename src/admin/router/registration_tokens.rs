@@ -0,0 +1,221 @@
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::auth::clients;
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// The longest an initial access token may live.
+const MAX_LIFETIME: Duration = Duration::days(90);
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateRegistrationTokenRequestBody {
+    /// Who the token is for, e.g. the partner's name.
+    #[validate(length(min = 1, max = 100, message = "Should have from 1 to 100 characters"))]
+    name: String,
+    /// How many applications may be registered with the token. Omit for no limit.
+    #[validate(range(min = 1, message = "Should be at least 1"))]
+    max_registrations: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateRegistrationTokenResponse {
+    id: String,
+    name: String,
+    /// Shown once; only its hash is stored. Partners send it as `Authorization: Bearer <token>`
+    /// to `POST /auth/clients`.
+    token: String,
+    max_registrations: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RegistrationTokenItem {
+    id: String,
+    name: String,
+    max_registrations: Option<i32>,
+    /// Applications registered with the token so far.
+    registrations: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    revoked_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct RegistrationTokenPath {
+    token_id: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/registration-tokens",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    request_body = CreateRegistrationTokenRequestBody,
+    responses(
+        (status = 201, description = "Initial access token created; the raw token is only in this response. Registrations also need `allow_dynamic_registration` on the project", body = CreateRegistrationTokenResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn create_registration_token_handler(
+    member: RequirePermission<perm::AppCreate, ProjectMember>,
+    State(state): State<AppState>,
+    Json(body): Json<CreateRegistrationTokenRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+
+    let now = OffsetDateTime::now_utc();
+    let mut errors = HashMap::new();
+    if body.expires_at <= now {
+        errors.insert("expires_at".to_string(), vec!["must be in the future".to_string()]);
+    } else if body.expires_at > now + MAX_LIFETIME {
+        errors.insert(
+            "expires_at".to_string(),
+            vec![format!("must be within {} days", MAX_LIFETIME.whole_days())],
+        );
+    }
+    if !errors.is_empty() {
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let token_id = id::new_uuid();
+    let (token, token_hash) = clients::generate_initial_access_token(token_id)?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO client_registration_tokens
+                (id, project_id, name, token_hash, created_by_admin_user_id, max_registrations, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        token_id,
+        member.project_id,
+        body.name,
+        token_hash,
+        member.admin_id,
+        body.max_registrations,
+        body.expires_at,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateRegistrationTokenResponse {
+            id: token_id.to_string(),
+            name: body.name,
+            token,
+            max_registrations: body.max_registrations,
+            expires_at: body.expires_at,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/registration-tokens",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "The project's initial access tokens, newest first, including revoked and expired ones", body = Vec<RegistrationTokenItem>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_registration_tokens_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let tokens: Vec<RegistrationTokenItem> = sqlx::query!(
+        r#"
+            SELECT id, name, max_registrations, registrations, expires_at, revoked_at, created_at
+            FROM client_registration_tokens
+            WHERE project_id = $1
+            ORDER BY created_at DESC, id DESC
+        "#,
+        member.project_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| RegistrationTokenItem {
+        id: r.id.to_string(),
+        name: r.name,
+        max_registrations: r.max_registrations,
+        registrations: r.registrations,
+        expires_at: r.expires_at,
+        revoked_at: r.revoked_at,
+        created_at: r.created_at,
+    })
+    .collect();
+
+    Ok((StatusCode::OK, Json(tokens)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/registration-tokens/{token_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("token_id" = String, Path, description = "Token ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Token revoked; applications already registered with it keep working"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No active token with this ID in the project"),
+    )
+)]
+pub async fn revoke_registration_token_handler(
+    member: RequirePermission<perm::AppCreate, ProjectMember>,
+    Path(RegistrationTokenPath { token_id }): Path<RegistrationTokenPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let token_id = id::parse_uuid(&token_id)?;
+
+    sqlx::query!(
+        r#"
+            UPDATE client_registration_tokens SET revoked_at = NOW()
+            WHERE id = $1 AND project_id = $2 AND revoked_at IS NULL
+            RETURNING id
+        "#,
+        token_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
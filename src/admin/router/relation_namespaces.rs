@@ -0,0 +1,156 @@
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::authz::relations::NamespaceConfig;
+use crate::error::AppError;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct RelationNamespaceResponse {
+    name: String,
+    config: NamespaceConfig,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    updated_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct NamespacePath {
+    namespace: String,
+}
+
+fn namespace_response(
+    name: String,
+    config: serde_json::Value,
+    updated_at: time::OffsetDateTime,
+) -> RelationNamespaceResponse {
+    RelationNamespaceResponse {
+        name,
+        // Only validated configurations are stored.
+        config: serde_json::from_value(config).expect("stored namespace config is always valid"),
+        updated_at,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/relation-namespaces",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Relation namespaces configured in the project", body = Vec<RelationNamespaceResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_namespaces_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let namespaces: Vec<RelationNamespaceResponse> = sqlx::query!(
+        "SELECT name, config, updated_at FROM relation_namespaces WHERE project_id = $1 ORDER BY name",
+        member.project_id
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| namespace_response(r.name, r.config, r.updated_at))
+    .collect();
+
+    Ok((StatusCode::OK, Json(namespaces)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/relation-namespaces/{namespace}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("namespace" = String, Path, description = "Namespace name, e.g. `doc`"),
+    ),
+    responses(
+        (status = 200, description = "Namespace configuration", body = RelationNamespaceResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Namespace not configured in this project"),
+    )
+)]
+pub async fn get_namespace_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    Path(NamespacePath { namespace }): Path<NamespacePath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let record = sqlx::query!(
+        "SELECT name, config, updated_at FROM relation_namespaces WHERE project_id = $1 AND name = $2",
+        member.project_id,
+        namespace,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(namespace_response(record.name, record.config, record.updated_at)),
+    ))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orgs/{org_id}/projects/{project_id}/relation-namespaces/{namespace}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("namespace" = String, Path, description = "Namespace name, e.g. `doc`"),
+    ),
+    request_body = NamespaceConfig,
+    responses(
+        (status = 200, description = "Namespace configuration created or replaced; applies to checks immediately", body = RelationNamespaceResponse),
+        (status = 400, description = "Invalid namespace name or configuration"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn put_namespace_handler(
+    member: RequirePermission<perm::ProjectUpdate, ProjectMember>,
+    Path(NamespacePath { namespace }): Path<NamespacePath>,
+    State(state): State<AppState>,
+    Json(body): Json<NamespaceConfig>,
+) -> Result<impl IntoResponse, AppError> {
+    // Reuse the object parser so namespace names follow the same rules as tuple objects.
+    crate::authz::relations::ObjectRef::parse("namespace", &format!("{namespace}:_"))?;
+    body.validate()?;
+
+    let config = serde_json::to_value(&body).expect("namespace config always serializes");
+    let record = sqlx::query!(
+        r#"
+            INSERT INTO relation_namespaces (project_id, name, config)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (project_id, name)
+            DO UPDATE SET config = EXCLUDED.config, updated_at = NOW()
+            RETURNING name, config, updated_at
+        "#,
+        member.project_id,
+        namespace,
+        config,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(namespace_response(record.name, record.config, record.updated_at)),
+    ))
+}
@@ -0,0 +1,447 @@
+use super::account_scopes::permission_ids;
+use super::users::account_identity;
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::error::AppError;
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct RolePermissionsItem {
+    /// Application the permissions are defined on; must belong to the role's project.
+    app_id: String,
+    /// Permission names defined on the application.
+    scopes: Vec<String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RoleRequestBody {
+    #[validate(length(min = 1, max = 100, message = "Should have from 1 to 100 characters"))]
+    name: String,
+    description: Option<String>,
+    /// Default roles are assigned to every account registered in the project afterwards.
+    #[serde(default)]
+    is_default: bool,
+    #[serde(default)]
+    permissions: Vec<RolePermissionsItem>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RoleResponse {
+    id: String,
+    name: String,
+    description: Option<String>,
+    is_default: bool,
+    permissions: Vec<RolePermissionsItem>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct RoleIdPath {
+    role_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct AccountRolePath {
+    account_id: String,
+    role_id: String,
+}
+
+/// Resolves every permission group of the body into permission ids of the member's project.
+async fn role_permission_ids(
+    state: &AppState,
+    member: &ProjectMember,
+    body: &RoleRequestBody,
+) -> Result<Vec<Uuid>, AppError> {
+    let mut ids = Vec::new();
+    for group in &body.permissions {
+        ids.extend(permission_ids(state, member, &group.app_id, &group.scopes).await?);
+    }
+
+    Ok(ids)
+}
+
+/// Loads the project's roles, optionally narrowed to a single one, with their permissions grouped by application.
+async fn load_roles(state: &AppState, project_id: Uuid, role_id: Option<Uuid>) -> Result<Vec<RoleResponse>, AppError> {
+    let roles = sqlx::query!(
+        r#"
+            SELECT id, name, description, is_default, created_at
+            FROM project_roles
+            WHERE project_id = $1 AND ($2::uuid IS NULL OR id = $2)
+            ORDER BY name
+        "#,
+        project_id,
+        role_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let permissions = sqlx::query!(
+        r#"
+            SELECT rp.role_id, p.app_id, array_agg(p.name ORDER BY p.name) AS "scopes!"
+            FROM project_role_permissions rp
+            JOIN project_roles r ON r.id = rp.role_id
+            JOIN permissions p ON p.id = rp.permission_id
+            WHERE r.project_id = $1 AND ($2::uuid IS NULL OR r.id = $2)
+            GROUP BY rp.role_id, p.app_id
+            ORDER BY p.app_id
+        "#,
+        project_id,
+        role_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let roles = roles
+        .into_iter()
+        .map(|role| RoleResponse {
+            id: role.id.to_string(),
+            name: role.name,
+            description: role.description,
+            is_default: role.is_default,
+            permissions: permissions
+                .iter()
+                .filter(|p| p.role_id == role.id)
+                .map(|p| RolePermissionsItem {
+                    app_id: p.app_id.to_string(),
+                    scopes: p.scopes.clone(),
+                })
+                .collect(),
+            created_at: role.created_at,
+        })
+        .collect();
+
+    Ok(roles)
+}
+
+async fn load_role(state: &AppState, project_id: Uuid, role_id: Uuid) -> Result<RoleResponse, AppError> {
+    load_roles(state, project_id, Some(role_id))
+        .await?
+        .pop()
+        .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/roles",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    request_body = RoleRequestBody,
+    responses(
+        (status = 201, description = "Role created", body = RoleResponse),
+        (status = 400, description = "Validation error or unknown scope"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Application not found in this project"),
+        (status = 409, description = "A role with this name already exists in the project"),
+    )
+)]
+pub async fn create_role_handler(
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
+    State(state): State<AppState>,
+    Json(body): Json<RoleRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let permission_ids = role_permission_ids(&state, &member, &body).await?;
+    let role_id = id::new_uuid();
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO project_roles (id, project_id, name, description, is_default) VALUES ($1, $2, $3, $4, $5)",
+        role_id,
+        member.project_id,
+        body.name,
+        body.description,
+        body.is_default,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO project_role_permissions (role_id, permission_id)
+            SELECT $1, permission_id FROM UNNEST($2::uuid[]) AS t(permission_id)
+            ON CONFLICT DO NOTHING
+        "#,
+        role_id,
+        &permission_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let role = load_role(&state, member.project_id, role_id).await?;
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/roles",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Roles defined in the project", body = Vec<RoleResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_roles_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let roles = load_roles(&state, member.project_id, None).await?;
+    Ok((StatusCode::OK, Json(roles)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Role ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "Role details", body = RoleResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Role not found in this project"),
+    )
+)]
+pub async fn get_role_handler(
+    member: RequirePermission<perm::ProjectRead, ProjectMember>,
+    Path(RoleIdPath { role_id }): Path<RoleIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let role_id = id::parse_uuid(&role_id)?;
+    let role = load_role(&state, member.project_id, role_id).await?;
+    Ok((StatusCode::OK, Json(role)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orgs/{org_id}/projects/{project_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Role ID (UUID v7)"),
+    ),
+    request_body = RoleRequestBody,
+    responses(
+        (status = 200, description = "Role replaced; accounts holding it pick up the new permissions on their next token", body = RoleResponse),
+        (status = 400, description = "Validation error or unknown scope"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Role or application not found in this project"),
+        (status = 409, description = "A role with this name already exists in the project"),
+    )
+)]
+pub async fn update_role_handler(
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
+    Path(RoleIdPath { role_id }): Path<RoleIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<RoleRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let role_id = id::parse_uuid(&role_id)?;
+    let permission_ids = role_permission_ids(&state, &member, &body).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let updated = sqlx::query!(
+        "UPDATE project_roles SET name = $3, description = $4, is_default = $5 WHERE id = $1 AND project_id = $2",
+        role_id,
+        member.project_id,
+        body.name,
+        body.description,
+        body.is_default,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    sqlx::query!("DELETE FROM project_role_permissions WHERE role_id = $1", role_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO project_role_permissions (role_id, permission_id)
+            SELECT $1, permission_id FROM UNNEST($2::uuid[]) AS t(permission_id)
+            ON CONFLICT DO NOTHING
+        "#,
+        role_id,
+        &permission_ids,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    let role = load_role(&state, member.project_id, role_id).await?;
+    Ok((StatusCode::OK, Json(role)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Role ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Role deleted and unassigned from every account"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Role not found in this project"),
+    )
+)]
+pub async fn delete_role_handler(
+    member: RequirePermission<perm::AppScopesManage, ProjectMember>,
+    Path(RoleIdPath { role_id }): Path<RoleIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let role_id = id::parse_uuid(&role_id)?;
+
+    let deleted = sqlx::query!(
+        "DELETE FROM project_roles WHERE id = $1 AND project_id = $2",
+        role_id,
+        member.project_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Role ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Role assigned; assigning it again is a no-op"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account or role not found in this project"),
+    )
+)]
+pub async fn assign_role_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountRolePath { account_id, role_id }): Path<AccountRolePath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    let role_id = id::parse_uuid(&role_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    // The role must belong to the same project as the account.
+    let inserted = sqlx::query!(
+        r#"
+            INSERT INTO account_roles (account_id, role_id)
+            SELECT $1, id FROM project_roles WHERE id = $2 AND project_id = $3
+            ON CONFLICT DO NOTHING
+            RETURNING role_id
+        "#,
+        account_id,
+        role_id,
+        member.project_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    if inserted.is_none() {
+        let exists: bool = sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM project_roles WHERE id = $1 AND project_id = $2)",
+            role_id,
+            member.project_id,
+        )
+        .fetch_one(&state.pool)
+        .await?
+        .unwrap_or(false);
+
+        if !exists {
+            return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+        }
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/roles/{role_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+        ("role_id" = String, Path, description = "Role ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Role unassigned; unassigning a role the account does not hold is a no-op"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account not found in this project"),
+    )
+)]
+pub async fn unassign_role_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountRolePath { account_id, role_id }): Path<AccountRolePath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    let role_id = id::parse_uuid(&role_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    sqlx::query!(
+        "DELETE FROM account_roles WHERE account_id = $1 AND role_id = $2",
+        account_id,
+        role_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
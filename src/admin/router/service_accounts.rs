@@ -0,0 +1,634 @@
+use super::members::resolve_role;
+use crate::admin::authorization::{OrgMember, RequirePermission, Role};
+use crate::admin::policy::{self, Permission, perm};
+use crate::audit::{self, AuthEvent};
+use crate::error::{AppError, ValidationErrors};
+use crate::router::AppState;
+use crate::{config, crypto, id, jwt};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Form, Json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::{Duration, OffsetDateTime};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// `client_assertion_type` of a JWT client assertion (RFC 7523, section 2.2).
+const JWT_BEARER_ASSERTION: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+
+/// The furthest in the future an assertion's `exp` may be. Accepted `jti`s are kept until then.
+const MAX_ASSERTION_LIFETIME: Duration = Duration::minutes(10);
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateServiceAccountRequestBody {
+    #[validate(length(min = 6, max = 50, message = "Should have from 6 to 50 characters"))]
+    username: String,
+    /// `owner`, `admin` or a custom role of the organization. Defaults to `admin`. It may not
+    /// grant anything the caller lacks, so only owners create `owner` accounts.
+    role: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ServiceAccountResponse {
+    id: String,
+    username: String,
+    credentials: Vec<CredentialItem>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CredentialItem {
+    id: String,
+    /// `client_secret` or `public_key`.
+    kind: String,
+    /// Signing algorithm of a public key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    algorithm: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_used_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CreateCredentialRequestBody {
+    /// A generated secret for the client credentials grant.
+    ClientSecret,
+    /// A public key the account signs client assertions with.
+    PublicKey {
+        /// `RS256`, `ES256` or `EdDSA`.
+        algorithm: String,
+        /// SubjectPublicKeyInfo PEM.
+        public_key: String,
+    },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateCredentialResponse {
+    id: String,
+    /// Shown once for `client_secret` credentials; only its hash is stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ServiceAccountTokenRequest {
+    /// Must be `client_credentials`.
+    grant_type: String,
+    /// The service account ID. Required with `client_secret`; with an assertion it must match its `sub`.
+    client_id: Option<String>,
+    client_secret: Option<String>,
+    /// `urn:ietf:params:oauth:client-assertion-type:jwt-bearer` when sending `client_assertion`.
+    client_assertion_type: Option<String>,
+    /// A JWT signed with one of the account's keys: `iss` and `sub` are the account ID, `aud` is this
+    /// endpoint's URL, and `jti` is unique.
+    client_assertion: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ServiceAccountTokenResponse {
+    access_token: String,
+    token_type: &'static str,
+    /// Seconds until `access_token` expires.
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+pub struct ServiceAccountPath {
+    service_account_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct CredentialPath {
+    service_account_id: String,
+    credential_id: String,
+}
+
+/// The URL client assertions must name in `aud`.
+fn token_endpoint() -> String {
+    format!("{}/admin/service-accounts/token", config::env::env().jwt_issuer)
+}
+
+/// Resolves a service account of the org, or fails with not found.
+async fn service_account_in_org(state: &AppState, org_id: Uuid, service_account_id: &str) -> Result<Uuid, AppError> {
+    let service_account_id = id::parse_uuid(&service_account_id)?;
+
+    sqlx::query_scalar!(
+        "SELECT id FROM admin_users WHERE id = $1 AND kind = 'service' AND owner_org_id = $2",
+        service_account_id,
+        org_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(service_account_id)
+}
+
+/// Refuses to let the caller hand out more than they hold: the account's permissions must all be
+/// the caller's too, so an admin, a custom role or a narrowed personal access token holding
+/// `service_accounts.manage` cannot mint credentials for an owner or a broader admin.
+fn ensure_within_caller(member: &OrgMember, role: Role, permissions: &[Permission]) -> Result<(), AppError> {
+    if role == Role::Owner && member.role != Some(Role::Owner) {
+        return Err(AppError::Forbidden);
+    }
+    if !permissions
+        .iter()
+        .all(|permission| member.permissions.contains(permission))
+    {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(())
+}
+
+/// What the service account's membership in its org lets it do; nothing if it was removed.
+async fn service_account_grant(
+    state: &AppState,
+    org_id: Uuid,
+    service_account_id: Uuid,
+) -> Result<(Role, Vec<Permission>), AppError> {
+    let membership = sqlx::query!(
+        r#"
+            SELECT m.role, r.permissions AS "custom_permissions?"
+            FROM admin_org_memberships m
+            LEFT JOIN admin_org_roles r ON r.id = m.custom_role_id
+            WHERE m.admin_user_id = $1 AND m.org_id = $2
+        "#,
+        service_account_id,
+        org_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    let Some(membership) = membership else {
+        return Ok((Role::Admin, Vec::new()));
+    };
+    let role = Role::parse(&membership.role).ok_or(AppError::InvalidToken)?;
+    Ok((role, policy::granted(role, membership.custom_permissions.as_deref())))
+}
+
+async fn load_service_accounts(state: &AppState, org_id: Uuid) -> Result<Vec<ServiceAccountResponse>, AppError> {
+    let accounts = sqlx::query!(
+        r#"
+            SELECT id, username
+            FROM admin_users
+            WHERE kind = 'service' AND owner_org_id = $1
+            ORDER BY username
+        "#,
+        org_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let ids: Vec<Uuid> = accounts.iter().map(|a| a.id).collect();
+    let credentials = sqlx::query!(
+        r#"
+            SELECT id, admin_user_id, kind, algorithm, created_at, last_used_at
+            FROM admin_service_account_credentials
+            WHERE admin_user_id = ANY($1)
+            ORDER BY created_at, id
+        "#,
+        &ids,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|a| ServiceAccountResponse {
+            id: a.id.to_string(),
+            username: a.username,
+            credentials: credentials
+                .iter()
+                .filter(|c| c.admin_user_id == a.id)
+                .map(|c| CredentialItem {
+                    id: c.id.to_string(),
+                    kind: c.kind.clone(),
+                    algorithm: c.algorithm.clone(),
+                    created_at: c.created_at,
+                    last_used_at: c.last_used_at,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/service-accounts",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    request_body = CreateServiceAccountRequestBody,
+    responses(
+        (status = 201, description = "Service account created as a member of the organization; add credentials next", body = ServiceAccountResponse),
+        (status = 400, description = "Validation error or unknown role"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks service_accounts.manage, or a permission the role grants"),
+        (status = 409, description = "The username is taken"),
+    )
+)]
+pub async fn create_service_account_handler(
+    member: RequirePermission<perm::ServiceAccountsManage>,
+    State(state): State<AppState>,
+    Json(body): Json<CreateServiceAccountRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let assignment = resolve_role(&state, member.org_id, body.role.as_deref().unwrap_or("admin")).await?;
+    ensure_within_caller(&member, assignment.role, &assignment.permissions)?;
+    let service_account_id = id::new_uuid();
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO admin_users (id, username, kind, owner_org_id) VALUES ($1, $2, 'service', $3)",
+        service_account_id,
+        body.username,
+        member.org_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO admin_org_memberships (id, admin_user_id, org_id, role, custom_role_id)
+            VALUES ($1, $2, $3, $4, $5)
+        "#,
+        id::new_uuid(),
+        service_account_id,
+        member.org_id,
+        assignment.role.as_str(),
+        assignment.custom_role_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ServiceAccountResponse {
+            id: service_account_id.to_string(),
+            username: body.username,
+            credentials: Vec::new(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/service-accounts",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(("org_id" = String, Path, description = "Organization ID (UUID v7)")),
+    responses(
+        (status = 200, description = "Service accounts owned by the organization", body = Vec<ServiceAccountResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_service_accounts_handler(
+    member: RequirePermission<perm::OrgRead>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let accounts = load_service_accounts(&state, member.org_id).await?;
+    Ok((StatusCode::OK, Json(accounts)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/service-accounts/{service_account_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("service_account_id" = String, Path, description = "Service account ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Service account deleted with its memberships and credentials"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks service_accounts.manage"),
+        (status = 404, description = "Service account not found in this organization"),
+    )
+)]
+pub async fn delete_service_account_handler(
+    member: RequirePermission<perm::ServiceAccountsManage>,
+    Path(ServiceAccountPath { service_account_id }): Path<ServiceAccountPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let service_account_id = service_account_in_org(&state, member.org_id, &service_account_id).await?;
+
+    sqlx::query!("DELETE FROM admin_users WHERE id = $1", service_account_id)
+        .execute(&state.pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/service-accounts/{service_account_id}/credentials",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("service_account_id" = String, Path, description = "Service account ID (UUID v7)"),
+    ),
+    request_body = CreateCredentialRequestBody,
+    responses(
+        (status = 201, description = "Credential added; existing credentials stay valid until deleted", body = CreateCredentialResponse),
+        (status = 400, description = "Unknown algorithm or a public key that does not parse"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks service_accounts.manage, or a permission the service account holds"),
+        (status = 404, description = "Service account not found in this organization"),
+    )
+)]
+pub async fn create_credential_handler(
+    member: RequirePermission<perm::ServiceAccountsManage>,
+    Path(ServiceAccountPath { service_account_id }): Path<ServiceAccountPath>,
+    State(state): State<AppState>,
+    Json(body): Json<CreateCredentialRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let service_account_id = service_account_in_org(&state, member.org_id, &service_account_id).await?;
+    let (role, permissions) = service_account_grant(&state, member.org_id, service_account_id).await?;
+    ensure_within_caller(&member, role, &permissions)?;
+    let credential_id = id::new_uuid();
+
+    let client_secret = match body {
+        CreateCredentialRequestBody::ClientSecret => {
+            let client_secret = crypto::generate_client_secret();
+            sqlx::query!(
+                r#"
+                    INSERT INTO admin_service_account_credentials (id, admin_user_id, kind, secret_hash)
+                    VALUES ($1, $2, 'client_secret', $3)
+                "#,
+                credential_id,
+                service_account_id,
+                crypto::hash_password(&client_secret)?,
+            )
+            .execute(&state.pool)
+            .await?;
+            Some(client_secret)
+        }
+        CreateCredentialRequestBody::PublicKey { algorithm, public_key } => {
+            if jwt::assertion_key(&algorithm, &public_key).is_none() {
+                let mut errors = HashMap::new();
+                errors.insert(
+                    "public_key".to_string(),
+                    vec!["must be a PEM public key for 'RS256', 'ES256' or 'EdDSA'".to_string()],
+                );
+                return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+            }
+            sqlx::query!(
+                r#"
+                    INSERT INTO admin_service_account_credentials (id, admin_user_id, kind, algorithm, public_key)
+                    VALUES ($1, $2, 'public_key', $3, $4)
+                "#,
+                credential_id,
+                service_account_id,
+                algorithm,
+                public_key,
+            )
+            .execute(&state.pool)
+            .await?;
+            None
+        }
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateCredentialResponse {
+            id: credential_id.to_string(),
+            client_secret,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/service-accounts/{service_account_id}/credentials/{credential_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("service_account_id" = String, Path, description = "Service account ID (UUID v7)"),
+        ("credential_id" = String, Path, description = "Credential ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Credential deleted; access tokens already issued with it run until they expire"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks service_accounts.manage"),
+        (status = 404, description = "Credential not found for this service account"),
+    )
+)]
+pub async fn delete_credential_handler(
+    member: RequirePermission<perm::ServiceAccountsManage>,
+    Path(CredentialPath {
+        service_account_id,
+        credential_id,
+    }): Path<CredentialPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let service_account_id = service_account_in_org(&state, member.org_id, &service_account_id).await?;
+    let credential_id = id::parse_uuid(&credential_id)?;
+
+    sqlx::query!(
+        "DELETE FROM admin_service_account_credentials WHERE id = $1 AND admin_user_id = $2 RETURNING id",
+        credential_id,
+        service_account_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks a client secret against the account's secrets; returns the account and the credential that matched.
+async fn authenticate_with_secret(state: &AppState, client_id: &str, secret: &str) -> Result<(Uuid, Uuid), AppError> {
+    let service_account_id = id::parse_uuid(&client_id).map_err(|_| AppError::InvalidToken)?;
+
+    let secrets = sqlx::query!(
+        r#"
+            SELECT c.id, c.secret_hash AS "secret_hash!"
+            FROM admin_service_account_credentials c
+            JOIN admin_users au ON au.id = c.admin_user_id
+            WHERE c.admin_user_id = $1 AND c.kind = 'client_secret' AND au.kind = 'service'
+        "#,
+        service_account_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let credential = secrets
+        .iter()
+        .find(|c| crypto::verify_password(secret, &c.secret_hash).is_ok())
+        .ok_or(AppError::InvalidToken)?;
+
+    Ok((service_account_id, credential.id))
+}
+
+/// Verifies a client assertion with the account's keys and records its `jti`, so it cannot be
+/// replayed; returns the account and the key that matched.
+async fn authenticate_with_assertion(
+    state: &AppState,
+    client_id: Option<&str>,
+    assertion: &str,
+) -> Result<(Uuid, Uuid), AppError> {
+    let subject = jwt::client_assertion_subject(assertion)?;
+    if client_id.is_some_and(|client_id| client_id != subject) {
+        return Err(AppError::InvalidToken);
+    }
+    let service_account_id = id::parse_uuid(&subject).map_err(|_| AppError::InvalidToken)?;
+
+    let keys = sqlx::query!(
+        r#"
+            SELECT c.id, c.algorithm AS "algorithm!", c.public_key AS "public_key!"
+            FROM admin_service_account_credentials c
+            JOIN admin_users au ON au.id = c.admin_user_id
+            WHERE c.admin_user_id = $1 AND c.kind = 'public_key' AND au.kind = 'service'
+        "#,
+        service_account_id,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    let audience = token_endpoint();
+    let (credential_id, claims) = keys
+        .iter()
+        .find_map(|k| {
+            let key = jwt::assertion_key(&k.algorithm, &k.public_key)?;
+            let claims = jwt::decode_client_assertion(assertion, &key, &subject, &audience).ok()?;
+            Some((k.id, claims))
+        })
+        .ok_or(AppError::InvalidToken)?;
+
+    let expires_at = OffsetDateTime::from_unix_timestamp(claims.exp as i64).map_err(|_| AppError::InvalidToken)?;
+    if expires_at > OffsetDateTime::now_utc() + MAX_ASSERTION_LIFETIME {
+        return Err(AppError::InvalidToken);
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM admin_service_account_assertions WHERE admin_user_id = $1 AND expires_at < NOW()",
+        service_account_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let recorded = sqlx::query_scalar!(
+        r#"
+            INSERT INTO admin_service_account_assertions (admin_user_id, jti, expires_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            RETURNING jti
+        "#,
+        service_account_id,
+        claims.jti,
+        expires_at,
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if recorded.is_none() {
+        return Err(AppError::InvalidToken);
+    }
+
+    tx.commit().await?;
+
+    Ok((service_account_id, credential_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/service-accounts/token",
+    tag = "admin",
+    request_body(content = ServiceAccountTokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Admin access token for the service account", body = ServiceAccountTokenResponse),
+        (status = 400, description = "Unsupported grant type, or not exactly one of client_secret and client_assertion"),
+        (status = 401, description = "Invalid client credentials or assertion"),
+    )
+)]
+pub async fn service_account_token_handler(
+    State(state): State<AppState>,
+    Form(body): Form<ServiceAccountTokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if body.grant_type != "client_credentials" {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "grant_type".to_string(),
+            vec!["must be 'client_credentials'".to_string()],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let authenticated = match (&body.client_secret, &body.client_assertion) {
+        (Some(secret), None) => match &body.client_id {
+            Some(client_id) => authenticate_with_secret(&state, client_id, secret).await,
+            None => Err(AppError::InvalidToken),
+        },
+        (None, Some(assertion)) if body.client_assertion_type.as_deref() == Some(JWT_BEARER_ASSERTION) => {
+            authenticate_with_assertion(&state, body.client_id.as_deref(), assertion).await
+        }
+        (None, Some(_)) => Err(AppError::InvalidToken),
+        _ => {
+            return Err(AppError::ValidationError(ValidationErrors::single_error(
+                "Send either client_secret or client_assertion".to_string(),
+            )));
+        }
+    };
+
+    let (service_account_id, credential_id) = match authenticated {
+        Ok(authenticated) => authenticated,
+        Err(err) => {
+            audit::write_auth_event(
+                &state.pool,
+                AuthEvent {
+                    event_type: "service_account_token",
+                    success: false,
+                    route: "/admin/service-accounts/token",
+                    identifier: body.client_id.as_deref(),
+                    http_status: Some(401),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            return Err(err);
+        }
+    };
+
+    sqlx::query!(
+        "UPDATE admin_service_account_credentials SET last_used_at = NOW() WHERE id = $1",
+        credential_id,
+    )
+    .execute(&state.pool)
+    .await?;
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "service_account_token",
+            success: true,
+            route: "/admin/service-accounts/token",
+            admin_user_id: Some(service_account_id),
+            http_status: Some(200),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ServiceAccountTokenResponse {
+            access_token: jwt::generate_service_account_token(&service_account_id.to_string())?,
+            token_type: "Bearer",
+            expires_in: u64::from(config::env::env().admin_access_token_duration_in_minutes) * 60,
+        }),
+    ))
+}
@@ -0,0 +1,712 @@
+use super::AdminLogItem;
+use crate::admin::authorization::{ProjectMember, RequirePermission};
+use crate::admin::policy::perm;
+use crate::audit::{self, AuthEvent};
+use crate::auth::scopes;
+use crate::error::AppError;
+use crate::pagination::{self, CursorPage, CursorParams};
+use crate::router::AppState;
+use crate::{crypto, id, jwt};
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+/// How long an impersonation token lasts, whatever the user token lifetime is.
+const IMPERSONATION_TOKEN_DURATION: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UserSearchParams {
+    /// Case-insensitive substring matched against the account's login identifiers.
+    pub identifier: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserAccountListItem {
+    id: String,
+    identity_id: String,
+    identifier: Option<String>,
+    is_active: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LoginMethodItem {
+    id: String,
+    method_type: String,
+    identifier: String,
+    is_verified: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserAccountResponse {
+    id: String,
+    identity_id: String,
+    is_active: bool,
+    #[schema(value_type = Option<Object>)]
+    profile: Option<serde_json::Value>,
+    login_methods: Vec<LoginMethodItem>,
+    scopes: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordRequestBody {
+    #[validate(length(min = 6, max = 50, message = "Should have from 6 to 50 characters"))]
+    password: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ImpersonateUserRequestBody {
+    /// `client_id` of the project application the token is for.
+    client_id: String,
+    /// Why the account is being accessed; recorded and shown to the user.
+    #[validate(length(min = 1, max = 500, message = "Should have from 1 to 500 characters"))]
+    reason: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImpersonateUserResponse {
+    /// `user_accounts` token naming the caller in its `act` claim.
+    access_token: String,
+    impersonation_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: time::OffsetDateTime,
+}
+
+/// Path params struct for handlers that need `account_id` in addition to what
+/// `ProjectMember` already extracts from `org_id` and `project_id`.
+#[derive(Deserialize)]
+pub struct AccountIdPath {
+    pub(super) account_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginMethodPath {
+    account_id: String,
+    login_method_id: String,
+}
+
+/// Loads the identity behind an account, making sure the account belongs to the member's project.
+pub(super) async fn account_identity(
+    state: &AppState,
+    member: &ProjectMember,
+    account_id: uuid::Uuid,
+) -> Result<uuid::Uuid, AppError> {
+    let identity_id = sqlx::query_scalar!(
+        "SELECT identity_id FROM user_accounts WHERE id = $1 AND project_id = $2",
+        account_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(identity_id)
+}
+
+/// Like `account_identity`, for actions that change the identity's login methods. Those are
+/// shared by every account of the identity, so the action is refused when the identity also
+/// has accounts in other projects: a project admin only manages credentials their project owns.
+async fn owned_account_identity(
+    state: &AppState,
+    member: &ProjectMember,
+    account_id: uuid::Uuid,
+) -> Result<uuid::Uuid, AppError> {
+    let account = sqlx::query!(
+        r#"
+            SELECT ua.identity_id,
+                   EXISTS(
+                       SELECT 1 FROM user_accounts other
+                       WHERE other.identity_id = ua.identity_id AND other.project_id <> ua.project_id
+                   ) AS "shared!"
+            FROM user_accounts ua
+            WHERE ua.id = $1 AND ua.project_id = $2
+        "#,
+        account_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    if account.shared {
+        return Err(AppError::Forbidden);
+    }
+
+    Ok(account.identity_id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/users",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        CursorParams,
+        UserSearchParams,
+    ),
+    responses(
+        (status = 200, description = "End-user accounts registered in the project", body = CursorPage<UserAccountListItem>),
+        (status = 400, description = "Invalid cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+    )
+)]
+pub async fn list_users_handler(
+    member: RequirePermission<perm::UsersRead, ProjectMember>,
+    State(state): State<AppState>,
+    Query(params): Query<CursorParams>,
+    Query(search): Query<UserSearchParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit();
+
+    let items: Vec<UserAccountListItem> = if let Some(ref cursor) = params.cursor {
+        let (cursor_time, cursor_id) = pagination::decode_cursor(cursor)?;
+        sqlx::query!(
+            r#"
+                SELECT ua.id, ua.identity_id, ua.created_at, i.is_active,
+                       (SELECT lm.identifier FROM login_methods lm
+                        WHERE lm.identity_id = ua.identity_id
+                        ORDER BY lm.is_verified DESC, lm.identifier LIMIT 1) AS identifier
+                FROM user_accounts ua
+                JOIN identities i ON i.id = ua.identity_id
+                WHERE ua.project_id = $1
+                  AND ($2::text IS NULL OR EXISTS (
+                        SELECT 1 FROM login_methods lm
+                        WHERE lm.identity_id = ua.identity_id
+                          AND lm.identifier ILIKE '%' || $2 || '%'))
+                  AND (ua.created_at, ua.id) < ($3, $4)
+                ORDER BY ua.created_at DESC, ua.id DESC
+                LIMIT $5
+            "#,
+            member.project_id,
+            search.identifier,
+            cursor_time,
+            cursor_id,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| UserAccountListItem {
+            id: row.id.to_string(),
+            identity_id: row.identity_id.to_string(),
+            identifier: row.identifier,
+            is_active: row.is_active,
+            created_at: row.created_at,
+        })
+        .collect()
+    } else {
+        sqlx::query!(
+            r#"
+                SELECT ua.id, ua.identity_id, ua.created_at, i.is_active,
+                       (SELECT lm.identifier FROM login_methods lm
+                        WHERE lm.identity_id = ua.identity_id
+                        ORDER BY lm.is_verified DESC, lm.identifier LIMIT 1) AS identifier
+                FROM user_accounts ua
+                JOIN identities i ON i.id = ua.identity_id
+                WHERE ua.project_id = $1
+                  AND ($2::text IS NULL OR EXISTS (
+                        SELECT 1 FROM login_methods lm
+                        WHERE lm.identity_id = ua.identity_id
+                          AND lm.identifier ILIKE '%' || $2 || '%'))
+                ORDER BY ua.created_at DESC, ua.id DESC
+                LIMIT $3
+            "#,
+            member.project_id,
+            search.identifier,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| UserAccountListItem {
+            id: row.id.to_string(),
+            identity_id: row.identity_id.to_string(),
+            identifier: row.identifier,
+            is_active: row.is_active,
+            created_at: row.created_at,
+        })
+        .collect()
+    };
+
+    let page = CursorPage::from_rows(items, limit, |item| {
+        let id = uuid::Uuid::parse_str(&item.id).expect("id from DB is always a valid UUID");
+        pagination::encode_cursor(item.created_at, id)
+    });
+
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    responses(
+        (status = 200, description = "End-user account details", body = UserAccountResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account not found in this project"),
+    )
+)]
+pub async fn get_user_handler(
+    member: RequirePermission<perm::UsersRead, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+
+    let record = sqlx::query!(
+        r#"
+            SELECT ua.id, ua.identity_id, ua.local_profile_data, ua.created_at, i.is_active
+            FROM user_accounts ua
+            JOIN identities i ON i.id = ua.identity_id
+            WHERE ua.id = $1 AND ua.project_id = $2
+        "#,
+        account_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    let login_methods = sqlx::query!(
+        "SELECT id, method_type, identifier, is_verified FROM login_methods WHERE identity_id = $1 ORDER BY method_type, identifier",
+        record.identity_id,
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| LoginMethodItem {
+        id: r.id.to_string(),
+        method_type: r.method_type,
+        identifier: r.identifier,
+        is_verified: r.is_verified,
+    })
+    .collect();
+
+    let scopes = crate::auth::scopes::for_account(&state.pool, account_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(UserAccountResponse {
+            id: record.id.to_string(),
+            identity_id: record.identity_id.to_string(),
+            is_active: record.is_active,
+            profile: record.local_profile_data,
+            login_methods,
+            scopes,
+            created_at: record.created_at,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/login-methods/{login_method_id}/verify",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+        ("login_method_id" = String, Path, description = "Login method ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Login method marked as verified"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden, or the account's identity also has accounts in other projects"),
+        (status = 404, description = "Account or login method not found"),
+    )
+)]
+pub async fn verify_login_method_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(LoginMethodPath {
+        account_id,
+        login_method_id,
+    }): Path<LoginMethodPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    let login_method_id = id::parse_uuid(&login_method_id)?;
+
+    let identity_id = owned_account_identity(&state, &member, account_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let identifier = sqlx::query_scalar!(
+        "UPDATE login_methods SET is_verified = true WHERE id = $1 AND identity_id = $2 RETURNING identifier",
+        login_method_id,
+        identity_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "user_login_method_verified",
+            success: true,
+            route: "/admin/orgs/{org_id}/projects/{project_id}/users/{account_id}/login-methods/{login_method_id}/verify",
+            admin_user_id: Some(member.admin_id),
+            user_account_id: Some(account_id),
+            identifier: Some(identifier.as_str()),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/password",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    request_body = ResetPasswordRequestBody,
+    responses(
+        (status = 204, description = "Password replaced on every password-based login method of the account's identity"),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden, or the account's identity also has accounts in other projects"),
+        (status = 404, description = "Account not found, or it has no password-based login method"),
+    )
+)]
+pub async fn reset_user_password_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<ResetPasswordRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let account_id = id::parse_uuid(&account_id)?;
+
+    let identity_id = owned_account_identity(&state, &member, account_id).await?;
+    let password_hash = crypto::hash_password(&body.password)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let updated = sqlx::query!(
+        "UPDATE login_methods SET password_hash = $1 WHERE identity_id = $2 AND password_hash IS NOT NULL",
+        password_hash,
+        identity_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Sqlx(sqlx::Error::RowNotFound));
+    }
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "user_password_reset",
+            success: true,
+            route: "/admin/orgs/{org_id}/projects/{project_id}/users/{account_id}/password",
+            admin_user_id: Some(member.admin_id),
+            user_account_id: Some(account_id),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/impersonate",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    request_body = ImpersonateUserRequestBody,
+    responses(
+        (status = 201, description = "Short-lived user token acting as the account; the user sees it in their session list", body = ImpersonateUserResponse),
+        (status = 400, description = "Validation error"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Caller lacks users.impersonate, or the project opted out of impersonation"),
+        (status = 404, description = "Account or application not found in this project"),
+    )
+)]
+pub async fn impersonate_user_handler(
+    member: RequirePermission<perm::UsersImpersonate, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+    Json(body): Json<ImpersonateUserRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    body.validate()?;
+    let account_id = id::parse_uuid(&account_id)?;
+    let client_id = id::parse_uuid(&body.client_id)?;
+
+    let allowed = sqlx::query_scalar!(
+        "SELECT allow_impersonation FROM projects WHERE id = $1",
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+    if !allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    account_identity(&state, &member, account_id).await?;
+    let application = sqlx::query!(
+        "SELECT id, name FROM applications WHERE client_id = $1 AND project_id = $2 AND deleted_at IS NULL",
+        client_id,
+        member.project_id,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+    let admin_username = sqlx::query_scalar!("SELECT username FROM admin_users WHERE id = $1", member.admin_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    let impersonation_id = id::new_uuid();
+    let expires_at = time::OffsetDateTime::now_utc() + IMPERSONATION_TOKEN_DURATION;
+    let scopes = scopes::for_application(&state.pool, account_id, application.id).await?;
+    let access_token = jwt::generate_impersonation_token(
+        &jwt::UserTokenSubject {
+            account_id: &account_id.to_string(),
+            project_id: &member.project_id.to_string(),
+            client_id: &client_id.to_string(),
+            scopes: &scopes,
+        },
+        &member.admin_id.to_string(),
+        IMPERSONATION_TOKEN_DURATION,
+    )?;
+
+    let mut tx = state.pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+            INSERT INTO user_impersonations
+                (id, user_account_id, admin_user_id, admin_username, application_id, reason, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        impersonation_id,
+        account_id,
+        member.admin_id,
+        admin_username,
+        application.id,
+        body.reason,
+        expires_at,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "user_impersonation",
+            success: true,
+            route: "/admin/orgs/{org_id}/projects/{project_id}/users/{account_id}/impersonate",
+            admin_user_id: Some(member.admin_id),
+            user_account_id: Some(account_id),
+            application_id: Some(application.id),
+            application_name: Some(application.name.as_str()),
+            identifier: Some(admin_username.as_str()),
+            http_status: Some(201),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ImpersonateUserResponse {
+            access_token,
+            impersonation_id: impersonation_id.to_string(),
+            expires_at,
+        }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+    ),
+    responses(
+        (status = 204, description = "Account deleted; its identity is deleted too when no other project uses it"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account not found in this project"),
+    )
+)]
+pub async fn delete_user_handler(
+    member: RequirePermission<perm::UsersManage, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Written before the delete, which nulls `user_account_id`; the identifier keeps the account id.
+    // Rolled back with the delete if the account is not in this project.
+    let identifier = account_id.to_string();
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "user_deleted",
+            success: true,
+            route: "/admin/orgs/{org_id}/projects/{project_id}/users/{account_id}",
+            admin_user_id: Some(member.admin_id),
+            user_account_id: Some(account_id),
+            identifier: Some(identifier.as_str()),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let identity_id = sqlx::query_scalar!(
+        "DELETE FROM user_accounts WHERE id = $1 AND project_id = $2 RETURNING identity_id",
+        account_id,
+        member.project_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Identities can be shared between projects; only drop the ones left without any account.
+    sqlx::query!(
+        "DELETE FROM identities WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM user_accounts WHERE identity_id = $1)",
+        identity_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/orgs/{org_id}/projects/{project_id}/users/{account_id}/events",
+    tag = "admin",
+    security(("bearer_auth" = [])),
+    params(
+        ("org_id" = String, Path, description = "Organization ID (UUID v7)"),
+        ("project_id" = String, Path, description = "Project ID (UUID v7)"),
+        ("account_id" = String, Path, description = "User account ID (UUID v7)"),
+        CursorParams,
+    ),
+    responses(
+        (status = 200, description = "Auth events recorded for the account", body = CursorPage<AdminLogItem>),
+        (status = 400, description = "Invalid cursor"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Account not found in this project"),
+    )
+)]
+pub async fn user_events_handler(
+    member: RequirePermission<perm::UsersRead, ProjectMember>,
+    Path(AccountIdPath { account_id }): Path<AccountIdPath>,
+    State(state): State<AppState>,
+    Query(params): Query<CursorParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let account_id = id::parse_uuid(&account_id)?;
+    account_identity(&state, &member, account_id).await?;
+
+    let limit = params.limit();
+
+    let items: Vec<AdminLogItem> = if let Some(ref cursor) = params.cursor {
+        let (cursor_time, cursor_id) = pagination::decode_cursor(cursor)?;
+        sqlx::query!(
+            r#"
+                SELECT id, event_type, identifier, application_id, application_name, ip_address, occurred_at
+                FROM auth_events
+                WHERE user_account_id = $1
+                  AND (occurred_at, id) < ($2, $3)
+                ORDER BY occurred_at DESC, id DESC
+                LIMIT $4
+            "#,
+            account_id,
+            cursor_time,
+            cursor_id,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| AdminLogItem {
+            id: row.id.to_string(),
+            event_type: row.event_type,
+            identifier: row.identifier,
+            application_id: row.application_id.map(|v: uuid::Uuid| v.to_string()),
+            application_name: row.application_name,
+            ip_address: row.ip_address,
+            occurred_at: row.occurred_at,
+        })
+        .collect()
+    } else {
+        sqlx::query!(
+            r#"
+                SELECT id, event_type, identifier, application_id, application_name, ip_address, occurred_at
+                FROM auth_events
+                WHERE user_account_id = $1
+                ORDER BY occurred_at DESC, id DESC
+                LIMIT $2
+            "#,
+            account_id,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| AdminLogItem {
+            id: row.id.to_string(),
+            event_type: row.event_type,
+            identifier: row.identifier,
+            application_id: row.application_id.map(|v: uuid::Uuid| v.to_string()),
+            application_name: row.application_name,
+            ip_address: row.ip_address,
+            occurred_at: row.occurred_at,
+        })
+        .collect()
+    };
+
+    let page = CursorPage::from_rows(items, limit, |item| {
+        let id = uuid::Uuid::parse_str(&item.id).expect("id from DB is always a valid UUID");
+        pagination::encode_cursor(item.occurred_at, id)
+    });
+
+    Ok((StatusCode::OK, Json(page)))
+}
@@ -0,0 +1,83 @@
+use super::policy::Permission;
+use crate::crypto;
+use crate::error::AppError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Every personal access token starts with this, so the middleware can tell one from a JWT.
+pub const PREFIX: &str = "pat_";
+
+/// What a personal access token narrows its admin down to. The middleware puts it in the request
+/// extensions; membership extractors then reject other orgs and drop permissions it lacks.
+#[derive(Debug, Clone)]
+pub struct TokenScope {
+    pub token_id: Uuid,
+    pub org_ids: Vec<Uuid>,
+    pub permissions: Vec<Permission>,
+}
+
+impl TokenScope {
+    /// The caller's permissions in `org_id` as far as the token allows them.
+    pub fn narrow(&self, org_id: Uuid, permissions: Vec<Permission>) -> Result<Vec<Permission>, AppError> {
+        if !self.org_ids.contains(&org_id) {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(permissions
+            .into_iter()
+            .filter(|permission| self.permissions.contains(permission))
+            .collect())
+    }
+}
+
+/// Builds the raw token handed to the admin once: `pat_<token id>_<secret>`. Only the secret's
+/// hash is stored; the id makes the lookup a primary-key read.
+pub fn generate(token_id: Uuid) -> Result<(String, String), AppError> {
+    let secret = crypto::generate_client_secret();
+    let secret_hash = crypto::hash_password(&secret)?;
+    Ok((format!("{PREFIX}{}_{secret}", token_id.simple()), secret_hash))
+}
+
+fn parse(raw: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = raw.strip_prefix(PREFIX)?.split_once('_')?;
+    Some((Uuid::try_parse(id).ok()?, secret))
+}
+
+/// Resolves a raw token to its admin and scope, recording the use. Unknown, revoked and expired
+/// tokens, and wrong secrets, are all rejected alike.
+pub async fn authenticate(pool: &PgPool, raw: &str) -> Result<(Uuid, TokenScope), AppError> {
+    let (token_id, secret) = parse(raw).ok_or(AppError::InvalidToken)?;
+
+    let token = sqlx::query!(
+        r#"
+            SELECT t.admin_user_id, t.token_hash, t.permissions,
+                   ARRAY(SELECT o.org_id FROM admin_personal_access_token_orgs o WHERE o.token_id = t.id) AS "org_ids!"
+            FROM admin_personal_access_tokens t
+            WHERE t.id = $1 AND t.revoked_at IS NULL AND t.expires_at > NOW()
+        "#,
+        token_id,
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::InvalidToken)?;
+
+    crypto::verify_password(secret, &token.token_hash).map_err(|_| AppError::InvalidToken)?;
+
+    sqlx::query!(
+        "UPDATE admin_personal_access_tokens SET last_used_at = NOW() WHERE id = $1",
+        token_id,
+    )
+    .execute(pool)
+    .await?;
+
+    let scope = TokenScope {
+        token_id,
+        org_ids: token.org_ids,
+        permissions: token
+            .permissions
+            .iter()
+            .filter_map(|name| Permission::parse(name))
+            .collect(),
+    };
+    Ok((token.admin_user_id, scope))
+}
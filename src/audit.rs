@@ -0,0 +1,38 @@
+use crate::error::AppError;
+use crate::id;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// A row to be written to `auth_events`. Fields left as `None` are stored as NULL.
+#[derive(Default)]
+pub struct AuthEvent<'a> {
+    pub event_type: &'a str,
+    pub success: bool,
+    pub route: &'a str,
+    pub admin_user_id: Option<Uuid>,
+    pub user_account_id: Option<Uuid>,
+    pub application_id: Option<Uuid>,
+    pub application_name: Option<&'a str>,
+    pub identifier: Option<&'a str>,
+    pub http_status: Option<i32>,
+}
+
+pub async fn write_auth_event(executor: impl PgExecutor<'_>, event: AuthEvent<'_>) -> Result<(), AppError> {
+    sqlx::query!(
+        "INSERT INTO auth_events (id, event_type, success, route, admin_user_id, user_account_id, application_id, application_name, identifier, http_status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        id::new_uuid(),
+        event.event_type,
+        event.success,
+        event.route,
+        event.admin_user_id,
+        event.user_account_id,
+        event.application_id,
+        event.application_name,
+        event.identifier,
+        event.http_status,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
@@ -1 +1,5 @@
+pub mod authorization;
+pub mod clients;
+pub mod consent;
 pub mod router;
+pub mod scopes;
@@ -0,0 +1,194 @@
+use crate::error::AppError;
+use crate::router::AppState;
+use crate::{crypto, id, jwt};
+use axum::extract::FromRequestParts;
+use axum::http::HeaderMap;
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use uuid::Uuid;
+
+/// Extractor that authenticates an end user from the bearer token.
+/// User-side counterpart of `admin::authorization::AdminId`: the token must be signed
+/// with the user key, carry `user_type == "user"`, and its `sub` is a `user_accounts.id`.
+/// The token's project and audience must still match the account and an application of that project,
+/// and for a third-party application the user's consent must predate the token and not be revoked.
+pub struct AccountId {
+    pub account_id: Uuid,
+    pub project_id: Uuid,
+    pub client_id: Uuid,
+}
+
+impl AccountId {
+    /// Verifies a user token the way the extractor does, also returning its claims. Used where the
+    /// token arrives in the body rather than the `Authorization` header, like token exchange.
+    pub async fn from_token(state: &AppState, token: &str) -> Result<(Self, jwt::Claims), AppError> {
+        // The audience must be a live application of the token's project. The project is read
+        // before the signature is checked, but the token is rejected unless `aud` is one of its applications.
+        let claimed_project_id =
+            jwt::unverified_project_id(token).and_then(|v| id::parse_uuid(&v).map_err(|_| AppError::InvalidToken))?;
+        let audiences = sqlx::query_scalar!(
+            r#"
+            SELECT a.client_id::text AS "client_id!"
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.project_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+            "#,
+            claimed_project_id
+        )
+        .fetch_all(&state.pool)
+        .await?;
+        let audiences: Vec<&str> = audiences.iter().map(String::as_str).collect();
+        let claims = jwt::decode_user_token(token, &audiences)?.claims;
+
+        if claims.user_type != "user" {
+            return Err(AppError::InvalidToken);
+        }
+
+        let account_id = id::parse_uuid(&claims.sub).map_err(|_| AppError::InvalidToken)?;
+        let project_id = claims
+            .project_id
+            .as_deref()
+            .and_then(|v| id::parse_uuid(&v).ok())
+            .ok_or(AppError::InvalidToken)?;
+        let client_id = claims
+            .aud
+            .as_deref()
+            .and_then(|v| id::parse_uuid(&v).ok())
+            .ok_or(AppError::InvalidToken)?;
+
+        let matches: bool = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS(
+                SELECT 1
+                FROM user_accounts ua
+                JOIN applications a ON a.project_id = ua.project_id
+                JOIN projects p ON p.id = a.project_id
+                JOIN organizations o ON o.id = p.org_id
+                WHERE ua.id = $1 AND ua.project_id = $2 AND a.client_id = $3
+                  AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+                  AND (
+                      a.first_party
+                      OR EXISTS (
+                          SELECT 1 FROM user_consents c
+                          WHERE c.user_account_id = ua.id AND c.application_id = a.id AND c.revoked_at IS NULL
+                            AND c.granted_at < to_timestamp($4::float8 + 1)
+                      )
+                  )
+            )
+            "#,
+            account_id,
+            project_id,
+            client_id,
+            claims.iat as f64
+        )
+        .fetch_one(&state.pool)
+        .await?
+        .unwrap_or(false);
+
+        if !matches {
+            return Err(AppError::InvalidToken);
+        }
+
+        let account = AccountId {
+            account_id,
+            project_id,
+            client_id,
+        };
+        Ok((account, claims))
+    }
+}
+
+impl FromRequestParts<AppState> for AccountId {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = jwt::get_jwt_token(&parts.headers)?;
+        let (account, _) = AccountId::from_token(state, token).await?;
+        Ok(account)
+    }
+}
+
+/// Extractor that authenticates an application with its client credentials, sent as
+/// HTTP Basic `client_id:client_secret`. Used by backend services calling on behalf of an application.
+pub struct ApplicationClient {
+    pub app_id: Uuid,
+    pub project_id: Uuid,
+    pub client_id: Uuid,
+    /// Grants the application registered for, see `auth::clients`.
+    pub grant_types: Vec<String>,
+}
+
+impl ApplicationClient {
+    /// Authenticates the client from the `Authorization` header. Used where only some requests to
+    /// an endpoint need client credentials, like the token endpoint's grants.
+    pub async fn from_headers(state: &AppState, headers: &HeaderMap) -> Result<Self, AppError> {
+        let header = headers
+            .get(AUTHORIZATION)
+            .ok_or(AppError::HeaderNotFound(AUTHORIZATION))?
+            .to_str()
+            .map_err(|_| AppError::InvalidToken)?;
+
+        let encoded = header.strip_prefix("Basic ").ok_or(AppError::InvalidToken)?;
+        let decoded = STANDARD.decode(encoded.trim()).map_err(|_| AppError::InvalidToken)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| AppError::InvalidToken)?;
+        let (client_id, client_secret) = decoded.split_once(':').ok_or(AppError::InvalidToken)?;
+        let client_id = id::parse_uuid(&client_id).map_err(|_| AppError::InvalidToken)?;
+
+        let app = sqlx::query!(
+            r#"
+            SELECT a.id, a.project_id, a.grant_types
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+            "#,
+            client_id
+        )
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::InvalidToken)?;
+
+        // Several secrets are active while one is being rotated; any of them authenticates.
+        let secrets = sqlx::query!(
+            r#"
+            SELECT id, secret_hash
+            FROM application_secrets
+            WHERE application_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at DESC
+            "#,
+            app.id
+        )
+        .fetch_all(&state.pool)
+        .await?;
+
+        let secret_id = secrets
+            .iter()
+            .find(|s| crypto::verify_password(client_secret, &s.secret_hash).is_ok())
+            .map(|s| s.id)
+            .ok_or(AppError::InvalidToken)?;
+
+        sqlx::query!(
+            "UPDATE application_secrets SET last_used_at = NOW() WHERE id = $1",
+            secret_id
+        )
+        .execute(&state.pool)
+        .await?;
+
+        Ok(ApplicationClient {
+            app_id: app.id,
+            project_id: app.project_id,
+            client_id,
+            grant_types: app.grant_types,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for ApplicationClient {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        ApplicationClient::from_headers(state, &parts.headers).await
+    }
+}
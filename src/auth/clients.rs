@@ -0,0 +1,51 @@
+use crate::crypto;
+use crate::error::AppError;
+use uuid::Uuid;
+
+// Client metadata shared by applications created by admins and those partners register
+// themselves (RFC 7591). Each application lists the grants it may use in `grant_types`.
+
+/// `POST /auth/login`.
+pub const PASSWORD_GRANT: &str = "password";
+/// RFC 8693, section 2.1.
+pub const TOKEN_EXCHANGE_GRANT: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+/// RFC 8628, section 3.4.
+pub const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// The client authenticates with HTTP Basic and one of its `application_secrets`.
+pub const CLIENT_SECRET_BASIC: &str = "client_secret_basic";
+/// A public client, such as a CLI or a TV app, that holds no secret.
+pub const AUTH_METHOD_NONE: &str = "none";
+
+/// Grants a partner may register for (RFC 7591). Token exchange is left to applications admins
+/// create: it mints tokens for the user without asking them, which would let a third-party client
+/// skip consent.
+pub const REGISTRABLE_GRANT_TYPES: [&str; 2] = [PASSWORD_GRANT, DEVICE_CODE_GRANT];
+
+/// Rejects a grant the application did not register for.
+pub fn require_grant(grant_types: &[String], grant: &str) -> Result<(), AppError> {
+    if grant_types.iter().any(|allowed| allowed == grant) {
+        Ok(())
+    } else {
+        Err(AppError::OAuth("unauthorized_client"))
+    }
+}
+
+/// Every initial access token starts with this.
+pub const INITIAL_ACCESS_TOKEN_PREFIX: &str = "reg_";
+
+/// Builds the raw initial access token handed to a partner once: `reg_<token id>_<secret>`.
+/// Only the secret's hash is stored; the id makes the lookup a primary-key read.
+pub fn generate_initial_access_token(token_id: Uuid) -> Result<(String, String), AppError> {
+    let secret = crypto::generate_client_secret();
+    let secret_hash = crypto::hash_password(&secret)?;
+    Ok((
+        format!("{INITIAL_ACCESS_TOKEN_PREFIX}{}_{secret}", token_id.simple()),
+        secret_hash,
+    ))
+}
+
+pub fn parse_initial_access_token(raw: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = raw.strip_prefix(INITIAL_ACCESS_TOKEN_PREFIX)?.split_once('_')?;
+    Some((Uuid::try_parse(id).ok()?, secret))
+}
@@ -0,0 +1,95 @@
+use crate::error::AppError;
+use crate::id;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+// Consent is only asked for third-party applications (`applications.first_party = false`). A
+// user approves a set of scopes at once; a later request is let through without asking again
+// when one approved set covers it.
+
+/// Sorts and deduplicates, so the same set is stored and compared the same way however it was requested.
+fn normalize(scopes: &[String]) -> Vec<String> {
+    let mut scopes = scopes.to_vec();
+    scopes.sort();
+    scopes.dedup();
+    scopes
+}
+
+/// Whether one of the user's active consents for the application covers every scope in `scopes`.
+pub async fn covers(
+    executor: impl PgExecutor<'_>,
+    account_id: Uuid,
+    app_id: Uuid,
+    scopes: &[String],
+) -> Result<bool, AppError> {
+    let covered = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_consents
+            WHERE user_account_id = $1 AND application_id = $2 AND revoked_at IS NULL AND scopes @> $3
+        ) AS "covered!"
+        "#,
+        account_id,
+        app_id,
+        &normalize(scopes),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(covered)
+}
+
+/// Records that the user approved `scopes` for the application. Approving a set again keeps the original record.
+pub async fn record(
+    executor: impl PgExecutor<'_>,
+    account_id: Uuid,
+    app_id: Uuid,
+    scopes: &[String],
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_consents (id, user_account_id, application_id, scopes)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_account_id, application_id, scopes) WHERE revoked_at IS NULL DO NOTHING
+        "#,
+        id::new_uuid(),
+        account_id,
+        app_id,
+        &normalize(scopes),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Every scope the user approved for the application across active consents, or `None` without any.
+pub async fn approved_scopes(
+    executor: impl PgExecutor<'_>,
+    account_id: Uuid,
+    app_id: Uuid,
+) -> Result<Option<Vec<String>>, AppError> {
+    let approved = sqlx::query!(
+        r#"
+        SELECT
+            EXISTS(
+                SELECT 1 FROM user_consents
+                WHERE user_account_id = $1 AND application_id = $2 AND revoked_at IS NULL
+            ) AS "consented!",
+            COALESCE(
+                (
+                    SELECT ARRAY_AGG(DISTINCT scope ORDER BY scope)
+                    FROM user_consents c, UNNEST(c.scopes) AS scope
+                    WHERE c.user_account_id = $1 AND c.application_id = $2 AND c.revoked_at IS NULL
+                ),
+                '{}'
+            ) AS "scopes!"
+        "#,
+        account_id,
+        app_id,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(approved.consented.then_some(approved.scopes))
+}
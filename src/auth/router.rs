@@ -1,20 +1,39 @@
+use crate::audit::{self, AuthEvent};
+use crate::auth::authorization::AccountId;
+use crate::auth::{clients, consent, scopes};
 use crate::error::AppError;
-use crate::jwt;
 use crate::router::AppState;
-use crate::{crypto, id};
+use crate::{crypto, id, jwt};
+use axum::Json;
 use axum::extract::State;
-use axum::http::{HeaderMap, StatusCode};
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::Json;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
+use uuid::Uuid;
+
+mod consents;
+mod device;
+mod html;
+mod registration;
+mod token;
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequestBody {
-    email: String,
+    identifier: String,
+    method_type: String,
     password: String,
+    client_id: String,
+    /// Space-delimited permission names to narrow the token to. Defaults to everything the account
+    /// holds on the application.
+    scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LoginResponse {
+    access_token: String,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -30,7 +49,23 @@ pub struct RegisterRequestBody {
 pub fn get_router() -> OpenApiRouter<AppState> {
     OpenApiRouter::new()
         .routes(routes!(me_handler))
+        .routes(routes!(sessions_handler))
         .routes(routes!(login_handler))
+        .routes(routes!(token::token_handler))
+        .routes(routes!(device::device_authorization_handler))
+        .routes(routes!(
+            device::verification_page_handler,
+            device::verify_user_code_handler
+        ))
+        .routes(routes!(consents::consent_page_handler, consents::consent_handler))
+        .routes(routes!(consents::list_consents_handler))
+        .routes(routes!(consents::revoke_consent_handler))
+        .routes(routes!(registration::register_client_handler))
+        .routes(routes!(
+            registration::get_client_handler,
+            registration::update_client_handler,
+            registration::delete_client_handler
+        ))
         .routes(routes!(register_handler))
 }
 
@@ -59,7 +94,8 @@ async fn register_handler(
 
     let hash = crypto::hash_password(&body.password)?;
     sqlx::query!(
-        "INSERT INTO login_methods (identity_id, method_type, identifier, password_hash) VALUES ($1, $2, $3, $4)",
+        "INSERT INTO login_methods (id, identity_id, method_type, identifier, password_hash) VALUES ($1, $2, $3, $4, $5)",
+        id::new_uuid(),
         identity_id,
         body.method_type,
         body.identifier,
@@ -68,12 +104,23 @@ async fn register_handler(
     .execute(&mut *tx)
     .await?;
 
-    let project_id = sqlx::query_scalar!("SELECT project_id FROM applications WHERE client_id = $1", client_id)
-        .fetch_one(&mut *tx)
-        .await?;
+    let project_id = sqlx::query_scalar!(
+        r#"
+            SELECT a.project_id
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        client_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
 
+    let account_id = id::new_uuid();
     sqlx::query!(
-        "INSERT INTO user_accounts (identity_id, project_id, local_profile_data) VALUES ($1, $2, $3)",
+        "INSERT INTO user_accounts (id, identity_id, project_id, local_profile_data) VALUES ($1, $2, $3, $4)",
+        account_id,
         identity_id,
         project_id,
         body.profile
@@ -81,6 +128,15 @@ async fn register_handler(
     .execute(&mut *tx)
     .await?;
 
+    // New accounts start with every role the project marked as default.
+    sqlx::query!(
+        "INSERT INTO account_roles (account_id, role_id) SELECT $1, id FROM project_roles WHERE project_id = $2 AND is_default",
+        account_id,
+        project_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
     tx.commit().await?;
 
     Ok(StatusCode::CREATED)
@@ -90,9 +146,11 @@ async fn register_handler(
 pub struct MeResponse {
     identity_id: String,
     account_id: String,
+    project_id: String,
     identifier: String,
     #[schema(value_type = Option<Object>)]
     profile: Option<serde_json::Value>,
+    scopes: Vec<String>,
 }
 
 #[utoipa::path(
@@ -105,43 +163,255 @@ pub struct MeResponse {
         (status = 401, description = "Unauthorized"),
     )
 )]
-async fn me_handler(header: HeaderMap, State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
-    let jwt = jwt::get_jwt_token(&header)?;
-    let sub = jwt::decode_admin_token(jwt)?.claims.sub;
-
-    let user_id = id::parse_uuid(&sub)?;
+async fn me_handler(
+    AccountId {
+        account_id, client_id, ..
+    }: AccountId,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
     let user_data = sqlx::query!(
         r#"
         SELECT
             ua.identity_id,
             ua.id as account_id,
+            ua.project_id,
             ua.local_profile_data,
             lm.identifier
         FROM user_accounts ua
+        JOIN identities i ON i.id = ua.identity_id
         JOIN login_methods lm ON lm.identity_id = ua.identity_id
-        WHERE ua.id = $1 AND lm.is_verified = true
+        WHERE ua.id = $1 AND i.is_active = true
+        ORDER BY lm.is_verified DESC
         LIMIT 1
         "#,
-        user_id
+        account_id
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    // A well-signed token whose account is gone or disabled is no longer valid.
+    let Some(user_data) = user_data else {
+        return Err(AppError::InvalidToken);
+    };
+
+    // Only the scopes of the application the token was issued to, as login puts in the token:
+    // for a third-party application, those the user approved.
+    let application = sqlx::query!(
+        "SELECT id, first_party FROM applications WHERE client_id = $1",
+        client_id
     )
     .fetch_one(&state.pool)
     .await?;
+    let mut scopes = scopes::for_application(&state.pool, account_id, application.id).await?;
+    if !application.first_party {
+        let approved = consent::approved_scopes(&state.pool, account_id, application.id)
+            .await?
+            .unwrap_or_default();
+        scopes.retain(|scope| approved.contains(scope));
+    }
 
     Ok(Json(MeResponse {
         identity_id: user_data.identity_id.to_string(),
         account_id: user_data.account_id.to_string(),
+        project_id: user_data.project_id.to_string(),
         identifier: user_data.identifier,
         profile: user_data.local_profile_data,
+        scopes,
     }))
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SessionItem {
+    id: String,
+    /// Always `impersonation` for now: password logins issue stateless tokens that are not tracked.
+    kind: String,
+    /// Username of the admin who acted as the user.
+    actor: String,
+    /// Reason the admin gave.
+    reason: String,
+    /// `client_id` of the application the token was issued for, unless it has since been purged.
+    client_id: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: time::OffsetDateTime,
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Tracked sessions of the current account, newest first, including admin impersonations", body = Vec<SessionItem>),
+        (status = 401, description = "Unauthorized"),
+    )
+)]
+async fn sessions_handler(
+    AccountId { account_id, .. }: AccountId,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let sessions: Vec<SessionItem> = sqlx::query!(
+        r#"
+        SELECT ui.id, ui.admin_username, ui.reason, a.client_id AS "client_id?", ui.created_at, ui.expires_at
+        FROM user_impersonations ui
+        LEFT JOIN applications a ON a.id = ui.application_id
+        WHERE ui.user_account_id = $1
+        ORDER BY ui.created_at DESC, ui.id DESC
+        "#,
+        account_id
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| SessionItem {
+        id: r.id.to_string(),
+        kind: "impersonation".to_string(),
+        actor: r.admin_username,
+        reason: r.reason,
+        client_id: r.client_id.map(|client_id| client_id.to_string()),
+        created_at: r.created_at,
+        expires_at: r.expires_at,
+    })
+    .collect();
+
+    Ok(Json(sessions))
+}
+
+/// Checks a password login against the project's accounts. Returns the account the identifier
+/// belongs to, if any, and the same account again only when the password matches an active identity.
+pub(super) async fn verify_password_login(
+    state: &AppState,
+    project_id: Uuid,
+    method_type: &str,
+    identifier: &str,
+    password: &str,
+) -> Result<(Option<Uuid>, Option<Uuid>), AppError> {
+    let account = sqlx::query!(
+        r#"
+        SELECT ua.id, lm.password_hash, i.is_active
+        FROM login_methods lm
+        JOIN identities i ON i.id = lm.identity_id
+        JOIN user_accounts ua ON ua.identity_id = lm.identity_id
+        WHERE lm.method_type = $1 AND lm.identifier = $2 AND ua.project_id = $3
+        "#,
+        method_type,
+        identifier,
+        project_id
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    let known_account_id = account.as_ref().map(|account| account.id);
+    let account_id = account.and_then(|account| {
+        let hash = account.password_hash?;
+        let valid = account.is_active && crypto::verify_password(password, &hash).is_ok();
+        valid.then_some(account.id)
+    });
+
+    Ok((known_account_id, account_id))
+}
+
 #[utoipa::path(
     post,
     path = "/login",
     tag = "auth",
     request_body = LoginRequestBody,
     responses(
-        (status = 200, description = "Login successful"),
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "Bad request, `unauthorized_client` if the application did not register for the password grant, or `consent_required` until the user approves a third-party application's scopes at `/auth/consent`"),
+        (status = 401, description = "Invalid credentials"),
     )
 )]
-async fn login_handler(Json(_body): Json<LoginRequestBody>) -> impl IntoResponse {}
+async fn login_handler(
+    State(state): State<AppState>,
+    Json(body): Json<LoginRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let client_id = id::parse_uuid(&body.client_id)?;
+
+    // Applications of deleted projects and orgs stop issuing tokens along with them.
+    let application = sqlx::query!(
+        r#"
+            SELECT a.id, a.name, a.project_id, a.grant_types, a.first_party
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        client_id
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    let Some(application) = application else {
+        return Err(AppError::InvalidToken);
+    };
+    clients::require_grant(&application.grant_types, clients::PASSWORD_GRANT)?;
+
+    let (known_account_id, account_id) = verify_password_login(
+        &state,
+        application.project_id,
+        &body.method_type,
+        &body.identifier,
+        &body.password,
+    )
+    .await?;
+
+    let scopes = match account_id {
+        Some(account_id) => {
+            let mut scopes = scopes::for_application(&state.pool, account_id, application.id).await?;
+            if let Some(requested) = body.scope.as_deref() {
+                let requested: Vec<&str> = requested.split_whitespace().collect();
+                scopes.retain(|scope| requested.contains(&scope.as_str()));
+            }
+            Some(scopes)
+        }
+        None => None,
+    };
+    // Third-party applications get tokens once the user approved the scopes on the consent page.
+    let consent_required = match (account_id, &scopes) {
+        (Some(account_id), Some(scopes)) if !application.first_party => {
+            !consent::covers(&state.pool, account_id, application.id, scopes).await?
+        }
+        _ => false,
+    };
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "user_login",
+            success: account_id.is_some() && !consent_required,
+            route: "/auth/login",
+            user_account_id: known_account_id,
+            application_id: Some(application.id),
+            application_name: Some(application.name.as_str()),
+            identifier: Some(body.identifier.as_str()),
+            http_status: Some(match (account_id, consent_required) {
+                (None, _) => 401,
+                (Some(_), true) => 400,
+                (Some(_), false) => 200,
+            }),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // Unknown identifier and wrong password are indistinguishable to the caller.
+    let (Some(account_id), Some(scopes)) = (account_id, scopes) else {
+        return Err(AppError::InvalidToken);
+    };
+    if consent_required {
+        return Err(AppError::OAuth("consent_required"));
+    }
+
+    let access_token = jwt::generate_user_token(&jwt::UserTokenSubject {
+        account_id: &account_id.to_string(),
+        project_id: &application.project_id.to_string(),
+        client_id: &client_id.to_string(),
+        scopes: &scopes,
+    })?;
+
+    Ok((StatusCode::OK, Json(LoginResponse { access_token })))
+}
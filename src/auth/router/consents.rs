@@ -0,0 +1,391 @@
+use crate::audit::{self, AuthEvent};
+use crate::auth::authorization::AccountId;
+use crate::auth::router::verify_password_login;
+use crate::auth::{consent, scopes};
+use crate::error::AppError;
+use crate::id;
+use crate::router::AppState;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse};
+use axum::{Form, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::html::{THIRD_PARTY_NOTICE, escape_html, page, sign_in_fields};
+
+struct ConsentApplication {
+    id: Uuid,
+    name: String,
+    project_id: Uuid,
+    first_party: bool,
+}
+
+/// Looks up a live application by `client_id` and checks that `scope` only names its permissions.
+/// Errors come back as the page to show.
+async fn consent_application(
+    state: &AppState,
+    client_id: &str,
+    scope: Option<&str>,
+) -> Result<Result<(ConsentApplication, Option<Vec<String>>), Html<String>>, AppError> {
+    let Ok(client_id) = id::parse_uuid(&client_id) else {
+        return Ok(Err(page("Authorize application", "<p>Unknown application.</p>")));
+    };
+
+    let application = sqlx::query_as!(
+        ConsentApplication,
+        r#"
+            SELECT a.id, a.name, a.project_id, a.first_party
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        client_id
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+    let Some(application) = application else {
+        return Ok(Err(page("Authorize application", "<p>Unknown application.</p>")));
+    };
+
+    let Some(scope) = scope.filter(|scope| !scope.trim().is_empty()) else {
+        return Ok(Ok((application, None)));
+    };
+    let requested: Vec<String> = scope.split_whitespace().map(str::to_string).collect();
+    let known = sqlx::query_scalar!(
+        "SELECT name FROM permissions WHERE app_id = $1 AND name = ANY($2)",
+        application.id,
+        &requested
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    if let Some(unknown) = requested.iter().find(|scope| !known.contains(scope)) {
+        let body = format!(
+            "<p><strong>{}</strong> asked for a permission it does not have: {}</p>",
+            escape_html(&application.name),
+            escape_html(unknown)
+        );
+        return Ok(Err(page("Authorize application", &body)));
+    }
+
+    Ok(Ok((application, Some(requested))))
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct ConsentQuery {
+    client_id: String,
+    /// Space-delimited permission names the application asks for. Defaults to everything the
+    /// account holds on the application.
+    scope: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/consent",
+    tag = "auth",
+    params(ConsentQuery),
+    responses(
+        (status = 200, description = "HTML page to sign in and allow or deny a third-party application's scopes", content_type = "text/html"),
+    )
+)]
+pub async fn consent_page_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ConsentQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let (application, requested) = match consent_application(&state, &query.client_id, query.scope.as_deref()).await? {
+        Ok(found) => found,
+        Err(page) => return Ok(page),
+    };
+
+    if application.first_party {
+        let body = format!(
+            "<p><strong>{}</strong> does not need your approval.</p>",
+            escape_html(&application.name)
+        );
+        return Ok(page("Authorize application", &body));
+    }
+
+    let scopes = match &requested {
+        Some(requested) => requested
+            .iter()
+            .map(|scope| format!("<li>{}</li>", escape_html(scope)))
+            .collect::<String>(),
+        None => "<li>Everything your account can do in this application</li>".to_string(),
+    };
+    let body = format!(
+        "<p><strong>{app}</strong> is asking for access:</p><ul>{scopes}</ul>{THIRD_PARTY_NOTICE}\
+         <form method=\"post\" action=\"consent\">\
+         <input type=\"hidden\" name=\"client_id\" value=\"{client_id}\">\
+         <input type=\"hidden\" name=\"scope\" value=\"{scope}\">{fields}\
+         <button type=\"submit\" name=\"action\" value=\"allow\">Allow</button> \
+         <button type=\"submit\" name=\"action\" value=\"deny\">Deny</button></form>",
+        app = escape_html(&application.name),
+        client_id = escape_html(&query.client_id),
+        scope = escape_html(&requested.map(|requested| requested.join(" ")).unwrap_or_default()),
+        fields = sign_in_fields(),
+    );
+
+    Ok(page("Authorize application", &body))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ConsentForm {
+    client_id: String,
+    scope: Option<String>,
+    method_type: String,
+    identifier: String,
+    password: String,
+    /// `allow` or `deny`.
+    action: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/consent",
+    tag = "auth",
+    request_body(content = ConsentForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "HTML page confirming the decision; the application can now sign the user in for these scopes without asking again", content_type = "text/html"),
+        (status = 400, description = "HTML page: unknown application, scope or action", content_type = "text/html"),
+        (status = 401, description = "HTML page: invalid credentials", content_type = "text/html"),
+    )
+)]
+pub async fn consent_handler(
+    State(state): State<AppState>,
+    Form(body): Form<ConsentForm>,
+) -> Result<impl IntoResponse, AppError> {
+    let allow = match body.action.as_str() {
+        "allow" => true,
+        "deny" => false,
+        _ => {
+            let page = page("Authorize application", "<p>Choose Allow or Deny.</p>");
+            return Ok((StatusCode::BAD_REQUEST, page));
+        }
+    };
+
+    let (application, requested) = match consent_application(&state, &body.client_id, body.scope.as_deref()).await? {
+        Ok(found) => found,
+        Err(page) => return Ok((StatusCode::BAD_REQUEST, page)),
+    };
+
+    let (known_account_id, account_id) = verify_password_login(
+        &state,
+        application.project_id,
+        &body.method_type,
+        &body.identifier,
+        &body.password,
+    )
+    .await?;
+
+    if let Some(account_id) = account_id
+        && allow
+    {
+        // Without requested scopes the consent covers what the account holds now, not later grants.
+        let scopes = match requested {
+            Some(requested) => requested,
+            None => scopes::for_application(&state.pool, account_id, application.id).await?,
+        };
+        consent::record(&state.pool, account_id, application.id, &scopes).await?;
+    }
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: if allow { "consent_granted" } else { "consent_denied" },
+            success: account_id.is_some(),
+            route: "/auth/consent",
+            user_account_id: known_account_id,
+            application_id: Some(application.id),
+            application_name: Some(application.name.as_str()),
+            identifier: Some(body.identifier.as_str()),
+            http_status: Some(if account_id.is_some() { 200 } else { 401 }),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let app = escape_html(&application.name);
+    Ok(match (account_id, allow) {
+        (None, _) => {
+            let retry = format!(
+                "<p>Invalid credentials.</p><p><a href=\"consent?client_id={}&amp;scope={}\">Try again</a></p>",
+                escape_html(&body.client_id),
+                escape_html(body.scope.as_deref().unwrap_or_default())
+            );
+            (StatusCode::UNAUTHORIZED, page("Authorize application", &retry))
+        }
+        (Some(_), true) => (
+            StatusCode::OK,
+            page(
+                "Application allowed",
+                &format!("<p><strong>{app}</strong> can now sign you in. You can return to it.</p>"),
+            ),
+        ),
+        (Some(_), false) => (
+            StatusCode::OK,
+            page(
+                "Application denied",
+                &format!("<p><strong>{app}</strong> was not given access.</p>"),
+            ),
+        ),
+    })
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GrantedApplication {
+    client_id: String,
+    name: String,
+    /// Every scope approved for the application.
+    scopes: Vec<String>,
+    /// When the earliest consent still in effect was given.
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    granted_at: time::OffsetDateTime,
+}
+
+/// Consents are only managed with tokens of the project's own applications. A third-party
+/// application must not see or revoke the user's grants to others with the token it was given.
+async fn require_first_party(state: &AppState, client_id: Uuid) -> Result<(), AppError> {
+    let first_party = sqlx::query_scalar!("SELECT first_party FROM applications WHERE client_id = $1", client_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+    if !first_party {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/consents",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Third-party applications the current account allowed, by name", body = Vec<GrantedApplication>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "The token was issued to a third-party application"),
+    )
+)]
+pub async fn list_consents_handler(
+    AccountId {
+        account_id, client_id, ..
+    }: AccountId,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    require_first_party(&state, client_id).await?;
+
+    let granted: Vec<GrantedApplication> = sqlx::query!(
+        r#"
+        SELECT a.client_id, a.name,
+            ARRAY(
+                SELECT DISTINCT scope
+                FROM user_consents c2, UNNEST(c2.scopes) AS scope
+                WHERE c2.user_account_id = $1 AND c2.application_id = a.id AND c2.revoked_at IS NULL
+                ORDER BY scope
+            ) AS "scopes!",
+            MIN(c.granted_at) AS "granted_at!"
+        FROM user_consents c
+        JOIN applications a ON a.id = c.application_id
+        WHERE c.user_account_id = $1 AND c.revoked_at IS NULL AND a.deleted_at IS NULL
+        GROUP BY a.id
+        ORDER BY a.name, a.client_id
+        "#,
+        account_id
+    )
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .map(|r| GrantedApplication {
+        client_id: r.client_id.to_string(),
+        name: r.name,
+        scopes: r.scopes,
+        granted_at: r.granted_at,
+    })
+    .collect();
+
+    Ok(Json(granted))
+}
+
+#[derive(Deserialize)]
+pub struct ConsentPath {
+    client_id: String,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/consents/{client_id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("client_id" = String, Path, description = "Client ID of the granted application (UUID v7)")),
+    responses(
+        (status = 204, description = "Every consent for the application revoked; its tokens for the account stop working and approved device codes can no longer be redeemed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "The token was issued to a third-party application"),
+        (status = 404, description = "The account has not allowed this application"),
+    )
+)]
+pub async fn revoke_consent_handler(
+    AccountId {
+        account_id,
+        client_id: audience,
+        ..
+    }: AccountId,
+    Path(ConsentPath { client_id }): Path<ConsentPath>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    require_first_party(&state, audience).await?;
+
+    let client_id = id::parse_uuid(&client_id)?;
+    let mut tx = state.pool.begin().await?;
+
+    let application = sqlx::query!(
+        r#"
+        UPDATE user_consents c SET revoked_at = NOW()
+        FROM applications a
+        WHERE a.id = c.application_id AND a.client_id = $2 AND c.user_account_id = $1 AND c.revoked_at IS NULL
+        RETURNING a.id, a.name
+        "#,
+        account_id,
+        client_id
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .next()
+    .ok_or(AppError::Sqlx(sqlx::Error::RowNotFound))?;
+
+    // Access tokens are checked against the consent on every use; a device that has not picked
+    // up its token yet is turned away as if the user had denied it.
+    sqlx::query!(
+        r#"
+        UPDATE device_authorizations SET status = 'denied'
+        WHERE user_account_id = $1 AND application_id = $2 AND status = 'approved'
+        "#,
+        account_id,
+        application.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "consent_revoked",
+            success: true,
+            route: "/auth/consents",
+            user_account_id: Some(account_id),
+            application_id: Some(application.id),
+            application_name: Some(application.name.as_str()),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,383 @@
+use crate::audit::{self, AuthEvent};
+use crate::auth::clients::{self, DEVICE_CODE_GRANT};
+use crate::auth::router::verify_password_login;
+use crate::auth::{consent, scopes};
+use crate::error::AppError;
+use crate::router::AppState;
+use crate::{config, crypto, id};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Form, Json};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use super::html::{THIRD_PARTY_NOTICE, escape_html, page, sign_in_fields};
+use super::token::field_error;
+
+/// How long a device code and its user code stay usable.
+const DEVICE_CODE_LIFETIME_SECS: i64 = 600;
+
+/// Minimum seconds between polls; `slow_down` raises it per request (RFC 8628, section 3.5).
+pub(super) const POLL_INTERVAL_SECS: i32 = 5;
+
+/// Consonants only, so user codes cannot spell words and are hard to misread.
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+#[derive(Deserialize, ToSchema)]
+pub struct DeviceAuthorizationRequest {
+    client_id: String,
+    /// Space-delimited permission names. Defaults to everything the account holds on the application.
+    scope: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeviceAuthorizationResponse {
+    /// Secret the device polls `/auth/token` with.
+    device_code: String,
+    /// Short code the user types on the verification page, e.g. `WDJB-MJHT`.
+    user_code: String,
+    verification_uri: String,
+    /// `verification_uri` with the user code filled in, for QR codes.
+    verification_uri_complete: String,
+    /// Seconds until both codes expire.
+    expires_in: i64,
+    /// Seconds the device waits between polls.
+    interval: i32,
+}
+
+fn generate_user_code() -> String {
+    let mut rng = rand::rng();
+    let code: String = (0..8)
+        .map(|_| USER_CODE_ALPHABET[rng.random_range(0..USER_CODE_ALPHABET.len())] as char)
+        .collect();
+    format!("{}-{}", &code[..4], &code[4..])
+}
+
+/// Accepts user codes typed in lowercase or without the dash.
+fn normalize_user_code(user_code: &str) -> String {
+    let code: String = user_code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if code.len() == 8 {
+        format!("{}-{}", &code[..4], &code[4..])
+    } else {
+        code
+    }
+}
+
+/// Splits a device code into the id of its row and the secret checked against the stored hash.
+pub(super) fn parse_device_code(device_code: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = device_code.split_once('.')?;
+    Some((Uuid::try_parse(id).ok()?, secret))
+}
+
+#[utoipa::path(
+    post,
+    path = "/device/code",
+    tag = "auth",
+    request_body(content = DeviceAuthorizationRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Device and user codes issued", body = DeviceAuthorizationResponse),
+        (status = 400, description = "Unknown scope, or `unauthorized_client` if the application did not register for the device code grant"),
+        (status = 401, description = "Unknown client_id"),
+    )
+)]
+pub async fn device_authorization_handler(
+    State(state): State<AppState>,
+    Form(body): Form<DeviceAuthorizationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let client_id = id::parse_uuid(&body.client_id)?;
+
+    let application = sqlx::query!(
+        r#"
+            SELECT a.id, a.grant_types
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        client_id
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    let Some(application) = application else {
+        return Err(AppError::InvalidToken);
+    };
+    clients::require_grant(&application.grant_types, DEVICE_CODE_GRANT)?;
+
+    let scopes: Option<Vec<String>> = match body.scope.as_deref() {
+        Some(requested) => {
+            let requested: Vec<String> = requested.split_whitespace().map(str::to_string).collect();
+            let known = sqlx::query_scalar!(
+                "SELECT name FROM permissions WHERE app_id = $1 AND name = ANY($2)",
+                application.id,
+                &requested
+            )
+            .fetch_all(&state.pool)
+            .await?;
+            if let Some(unknown) = requested.iter().find(|scope| !known.contains(scope)) {
+                return Err(field_error(
+                    "scope",
+                    &format!("'{unknown}' is not a permission of the application"),
+                ));
+            }
+            Some(requested)
+        }
+        None => None,
+    };
+
+    let device_id = id::new_uuid();
+    let secret = crypto::generate_client_secret();
+    let device_code_hash = crypto::hash_password(&secret)?;
+    let user_code = generate_user_code();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO device_authorizations (id, application_id, device_code_hash, user_code, scopes, poll_interval_secs, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, NOW() + make_interval(secs => $7))
+        "#,
+        device_id,
+        application.id,
+        device_code_hash,
+        user_code,
+        scopes.as_deref(),
+        POLL_INTERVAL_SECS,
+        DEVICE_CODE_LIFETIME_SECS as f64
+    )
+    .execute(&state.pool)
+    .await?;
+
+    let verification_uri = format!("{}/auth/device", config::env::env().jwt_issuer);
+    Ok((
+        StatusCode::OK,
+        Json(DeviceAuthorizationResponse {
+            device_code: format!("{}.{secret}", device_id.simple()),
+            verification_uri_complete: format!("{verification_uri}?user_code={user_code}"),
+            user_code,
+            verification_uri,
+            expires_in: DEVICE_CODE_LIFETIME_SECS,
+            interval: POLL_INTERVAL_SECS,
+        }),
+    ))
+}
+
+fn user_code_form() -> String {
+    "<form method=\"get\" action=\"device\">\
+     <label>Code <input name=\"user_code\" autocomplete=\"off\" autofocus></label> \
+     <button type=\"submit\">Continue</button></form>"
+        .to_string()
+}
+
+struct PendingAuthorization {
+    id: Uuid,
+    application_id: Uuid,
+    application_name: String,
+    project_id: Uuid,
+    first_party: bool,
+    scopes: Option<Vec<String>>,
+}
+
+/// Looks up a request the user can still approve or deny.
+async fn find_pending(state: &AppState, user_code: &str) -> Result<Option<PendingAuthorization>, AppError> {
+    let pending = sqlx::query_as!(
+        PendingAuthorization,
+        r#"
+        SELECT da.id, da.application_id, a.name AS application_name, a.project_id, a.first_party, da.scopes
+        FROM device_authorizations da
+        JOIN applications a ON a.id = da.application_id
+        WHERE da.user_code = $1 AND da.status = 'pending' AND da.expires_at > NOW() AND a.deleted_at IS NULL
+        "#,
+        normalize_user_code(user_code)
+    )
+    .fetch_optional(&state.pool)
+    .await?;
+
+    Ok(pending)
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct VerificationQuery {
+    /// Pre-filled from `verification_uri_complete`.
+    user_code: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/device",
+    tag = "auth",
+    params(VerificationQuery),
+    responses(
+        (status = 200, description = "HTML page to enter a user code, or to sign in and approve the device", content_type = "text/html"),
+    )
+)]
+pub async fn verification_page_handler(
+    State(state): State<AppState>,
+    Query(query): Query<VerificationQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(user_code) = query.user_code.filter(|code| !code.trim().is_empty()) else {
+        return Ok(page("Connect a device", &user_code_form()));
+    };
+
+    let Some(pending) = find_pending(&state, &user_code).await? else {
+        let body = format!("<p>This code is invalid or has expired.</p>{}", user_code_form());
+        return Ok(page("Connect a device", &body));
+    };
+
+    let scopes = match &pending.scopes {
+        Some(scopes) if !scopes.is_empty() => scopes
+            .iter()
+            .map(|scope| format!("<li>{}</li>", escape_html(scope)))
+            .collect::<String>(),
+        _ => "<li>Everything your account can do in this application</li>".to_string(),
+    };
+    let body = format!(
+        "<p><strong>{app}</strong> is asking for access:</p><ul>{scopes}</ul>{third_party}\
+         <form method=\"post\" action=\"device\">\
+         <input type=\"hidden\" name=\"user_code\" value=\"{code}\">{fields}\
+         <button type=\"submit\" name=\"action\" value=\"approve\">Approve</button> \
+         <button type=\"submit\" name=\"action\" value=\"deny\">Deny</button></form>",
+        app = escape_html(&pending.application_name),
+        third_party = if pending.first_party { "" } else { THIRD_PARTY_NOTICE },
+        code = escape_html(&normalize_user_code(&user_code)),
+        fields = sign_in_fields(),
+    );
+
+    Ok(page("Connect a device", &body))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VerificationForm {
+    user_code: String,
+    method_type: String,
+    identifier: String,
+    password: String,
+    /// `approve` or `deny`.
+    action: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/device",
+    tag = "auth",
+    request_body(content = VerificationForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "HTML page confirming the device was approved or denied", content_type = "text/html"),
+        (status = 400, description = "HTML page: the code is invalid or expired, or the action is unknown", content_type = "text/html"),
+        (status = 401, description = "HTML page: invalid credentials", content_type = "text/html"),
+    )
+)]
+pub async fn verify_user_code_handler(
+    State(state): State<AppState>,
+    Form(body): Form<VerificationForm>,
+) -> Result<impl IntoResponse, AppError> {
+    let approve = match body.action.as_str() {
+        "approve" => true,
+        "deny" => false,
+        _ => {
+            let page = page("Connect a device", "<p>Choose Approve or Deny.</p>");
+            return Ok((StatusCode::BAD_REQUEST, page));
+        }
+    };
+
+    let Some(pending) = find_pending(&state, &body.user_code).await? else {
+        let body = format!("<p>This code is invalid or has expired.</p>{}", user_code_form());
+        return Ok((StatusCode::BAD_REQUEST, page("Connect a device", &body)));
+    };
+
+    // Denying also requires signing in, so a stranger holding the code cannot cancel the request.
+    let (known_account_id, account_id) = verify_password_login(
+        &state,
+        pending.project_id,
+        &body.method_type,
+        &body.identifier,
+        &body.password,
+    )
+    .await?;
+
+    let status = match account_id {
+        Some(account_id) => {
+            // For a third-party application approving is also consent. A request without scopes
+            // is pinned to what the account holds now, so later grants are not consented to.
+            let consented = match (approve && !pending.first_party, &pending.scopes) {
+                (false, _) => None,
+                (true, Some(requested)) => Some(requested.clone()),
+                (true, None) => Some(scopes::for_application(&state.pool, account_id, pending.application_id).await?),
+            };
+
+            let mut tx = state.pool.begin().await?;
+            // The status guard keeps two submissions from both deciding the request.
+            let decided = sqlx::query!(
+                r#"
+                UPDATE device_authorizations SET status = $2, user_account_id = $3, scopes = COALESCE($4, scopes)
+                WHERE id = $1 AND status = 'pending'
+                "#,
+                pending.id,
+                if approve { "approved" } else { "denied" },
+                account_id,
+                consented.as_deref()
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if let Some(consented) = consented.as_deref()
+                && decided == 1
+            {
+                consent::record(&mut *tx, account_id, pending.application_id, consented).await?;
+            }
+            tx.commit().await?;
+            if decided == 0 { 400 } else { 200 }
+        }
+        None => 401,
+    };
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: if approve { "device_approved" } else { "device_denied" },
+            success: status == 200,
+            route: "/auth/device",
+            user_account_id: known_account_id,
+            application_id: Some(pending.application_id),
+            application_name: Some(pending.application_name.as_str()),
+            identifier: Some(body.identifier.as_str()),
+            http_status: Some(status),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let app = escape_html(&pending.application_name);
+    Ok(match status {
+        200 if approve => (
+            StatusCode::OK,
+            page(
+                "Device connected",
+                &format!("<p><strong>{app}</strong> is now signed in. You can return to your device.</p>"),
+            ),
+        ),
+        200 => (
+            StatusCode::OK,
+            page(
+                "Device denied",
+                &format!("<p><strong>{app}</strong> was not given access.</p>"),
+            ),
+        ),
+        400 => (
+            StatusCode::BAD_REQUEST,
+            page("Connect a device", "<p>This code has already been used.</p>"),
+        ),
+        _ => {
+            let retry = format!(
+                "<p>Invalid credentials.</p><p><a href=\"device?user_code={}\">Try again</a></p>",
+                escape_html(&normalize_user_code(&body.user_code))
+            );
+            (StatusCode::UNAUTHORIZED, page("Connect a device", &retry))
+        }
+    })
+}
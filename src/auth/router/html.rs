@@ -0,0 +1,37 @@
+use axum::response::Html;
+
+// Minimal server-rendered pages for flows that happen in the user's browser rather than in
+// the application: device verification and consent.
+
+pub(super) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps already escaped markup in a minimal standalone page.
+pub(super) fn page(title: &str, body: &str) -> Html<String> {
+    Html(format!(
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title></head><body><h1>{title}</h1>{body}</body></html>\n"
+    ))
+}
+
+/// Shown wherever approving a third-party application's request is also consent.
+pub(super) const THIRD_PARTY_NOTICE: &str = "<p>This application is not operated by us. It keeps this access until you \
+     revoke it from your granted applications.</p>";
+
+/// Inputs for a password sign-in, posted along with the rest of a form.
+pub(super) fn sign_in_fields() -> &'static str {
+    "<label>Login type <input name=\"method_type\" value=\"email\"></label><br>\
+     <label>Identifier <input name=\"identifier\" autocomplete=\"username\"></label><br>\
+     <label>Password <input name=\"password\" type=\"password\" autocomplete=\"current-password\"></label><br>"
+}
@@ -0,0 +1,478 @@
+use crate::admin::router::application_secrets;
+use crate::audit::{self, AuthEvent};
+use crate::auth::clients::{self, AUTH_METHOD_NONE, CLIENT_SECRET_BASIC, PASSWORD_GRANT, REGISTRABLE_GRANT_TYPES};
+use crate::error::AppError;
+use crate::router::AppState;
+use crate::{config, crypto, id, jwt};
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::{Host, Url};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Client metadata (RFC 7591, section 2). Other metadata is accepted and ignored.
+#[derive(Deserialize, ToSchema)]
+pub struct ClientMetadata {
+    /// Required when updating, and must match the path.
+    client_id: Option<String>,
+    /// Defaults to the `client_id`.
+    client_name: Option<String>,
+    #[serde(default)]
+    redirect_uris: Vec<String>,
+    /// `password`, `urn:ietf:params:oauth:grant-type:device_code` or both. Defaults to `password`.
+    grant_types: Option<Vec<String>>,
+    /// `client_secret_basic` (default) or `none` for public clients. Cannot be changed by an update.
+    token_endpoint_auth_method: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ClientInformationResponse {
+    client_id: String,
+    /// Only in the registration response; send it with HTTP Basic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<String>,
+    /// `0`: the secret does not expire. Present when the client has a secret.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret_expires_at: Option<i64>,
+    /// Seconds since the epoch.
+    client_id_issued_at: i64,
+    /// Only in the registration response; authorizes `registration_client_uri`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registration_access_token: Option<String>,
+    registration_client_uri: String,
+    client_name: String,
+    redirect_uris: Vec<String>,
+    grant_types: Vec<String>,
+    token_endpoint_auth_method: String,
+}
+
+#[derive(Deserialize)]
+pub struct ClientPath {
+    client_id: String,
+}
+
+/// Metadata that passed validation, with defaults filled in.
+struct ValidMetadata {
+    client_name: Option<String>,
+    redirect_uris: Vec<String>,
+    grant_types: Vec<String>,
+    token_endpoint_auth_method: String,
+}
+
+/// Redirect URIs must use `https`, or `http` on the loopback interface for native apps
+/// (RFC 8252, section 7.3). Fragments are not allowed (RFC 6749, section 3.1.2).
+fn is_allowed_redirect_uri(uri: &str) -> bool {
+    let Ok(url) = Url::parse(uri) else {
+        return false;
+    };
+    if url.fragment().is_some() {
+        return false;
+    }
+
+    match (url.scheme(), url.host()) {
+        ("https", Some(_)) => true,
+        ("http", Some(Host::Ipv4(ip))) => ip.is_loopback(),
+        ("http", Some(Host::Ipv6(ip))) => ip.is_loopback(),
+        ("http", Some(Host::Domain(domain))) => domain == "localhost",
+        _ => false,
+    }
+}
+
+/// Errors use the codes of RFC 7591, section 3.2.2.
+fn validate_metadata(body: ClientMetadata) -> Result<ValidMetadata, AppError> {
+    if !body.redirect_uris.iter().all(|uri| is_allowed_redirect_uri(uri)) {
+        return Err(AppError::OAuth("invalid_redirect_uri"));
+    }
+
+    let client_name = body.client_name.map(|name| name.trim().to_string());
+    if client_name.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::OAuth("invalid_client_metadata"));
+    }
+
+    let mut grant_types = Vec::new();
+    for grant in body.grant_types.unwrap_or_else(|| vec![PASSWORD_GRANT.to_string()]) {
+        if !REGISTRABLE_GRANT_TYPES.contains(&grant.as_str()) {
+            return Err(AppError::OAuth("invalid_client_metadata"));
+        }
+        if !grant_types.contains(&grant) {
+            grant_types.push(grant);
+        }
+    }
+    if grant_types.is_empty() {
+        return Err(AppError::OAuth("invalid_client_metadata"));
+    }
+
+    let token_endpoint_auth_method = body
+        .token_endpoint_auth_method
+        .unwrap_or_else(|| CLIENT_SECRET_BASIC.to_string());
+    if token_endpoint_auth_method != CLIENT_SECRET_BASIC && token_endpoint_auth_method != AUTH_METHOD_NONE {
+        return Err(AppError::OAuth("invalid_client_metadata"));
+    }
+
+    Ok(ValidMetadata {
+        client_name,
+        redirect_uris: body.redirect_uris,
+        grant_types,
+        token_endpoint_auth_method,
+    })
+}
+
+fn registration_client_uri(client_id: Uuid) -> String {
+    format!("{}/auth/clients/{client_id}", config::env::env().jwt_issuer)
+}
+
+#[utoipa::path(
+    post,
+    path = "/clients",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    request_body = ClientMetadata,
+    responses(
+        (status = 201, description = "Application registered as third-party, so users approve its scopes on the consent page; the secret and registration access token are only in this response", body = ClientInformationResponse),
+        (status = 400, description = "`invalid_redirect_uri` or `invalid_client_metadata`"),
+        (status = 401, description = "Invalid, expired, revoked or used-up initial access token"),
+        (status = 403, description = "The project does not allow dynamic registration"),
+    )
+)]
+pub async fn register_client_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ClientMetadata>,
+) -> Result<impl IntoResponse, AppError> {
+    let raw = jwt::get_jwt_token(&headers)?;
+    let (token_id, secret) = clients::parse_initial_access_token(raw).ok_or(AppError::InvalidToken)?;
+
+    let token = sqlx::query!(
+        r#"
+            SELECT t.project_id, t.token_hash, p.allow_dynamic_registration
+            FROM client_registration_tokens t
+            JOIN projects p ON p.id = t.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE t.id = $1 AND t.revoked_at IS NULL AND t.expires_at > NOW()
+              AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        token_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::InvalidToken)?;
+
+    crypto::verify_password(secret, &token.token_hash).map_err(|_| AppError::InvalidToken)?;
+    if !token.allow_dynamic_registration {
+        return Err(AppError::Forbidden);
+    }
+
+    let metadata = validate_metadata(body)?;
+
+    let application_id = id::new_uuid();
+    let client_id = id::new_uuid();
+    let client_name = metadata.client_name.unwrap_or_else(|| client_id.to_string());
+    let registration_access_token = crypto::generate_client_secret();
+    let registration_access_token_hash = crypto::hash_password(&registration_access_token)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Counting in the same statement as the limit check keeps concurrent registrations within it.
+    let counted = sqlx::query!(
+        r#"
+            UPDATE client_registration_tokens SET registrations = registrations + 1
+            WHERE id = $1 AND (max_registrations IS NULL OR registrations < max_registrations)
+        "#,
+        token_id,
+    )
+    .execute(&mut *tx)
+    .await?;
+    if counted.rows_affected() == 0 {
+        return Err(AppError::InvalidToken);
+    }
+
+    let created_at = sqlx::query_scalar!(
+        r#"
+            INSERT INTO applications
+                (id, project_id, name, client_id, redirect_uris, grant_types, token_endpoint_auth_method,
+                 registration_access_token_hash, registration_token_id, first_party)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, false)
+            RETURNING created_at
+        "#,
+        application_id,
+        token.project_id,
+        client_name,
+        client_id,
+        &metadata.redirect_uris,
+        &metadata.grant_types,
+        metadata.token_endpoint_auth_method,
+        registration_access_token_hash,
+        token_id,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let client_secret = if metadata.token_endpoint_auth_method == CLIENT_SECRET_BASIC {
+        Some(
+            application_secrets::insert_secret(&mut *tx, application_id, None)
+                .await?
+                .client_secret,
+        )
+    } else {
+        None
+    };
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "client_registration",
+            success: true,
+            route: "/auth/clients",
+            application_id: Some(application_id),
+            application_name: Some(client_name.as_str()),
+            http_status: Some(201),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ClientInformationResponse {
+            client_id: client_id.to_string(),
+            client_secret_expires_at: client_secret.as_ref().map(|_| 0),
+            client_secret,
+            client_id_issued_at: created_at.unix_timestamp(),
+            registration_access_token: Some(registration_access_token),
+            registration_client_uri: registration_client_uri(client_id),
+            client_name,
+            redirect_uris: metadata.redirect_uris,
+            grant_types: metadata.grant_types,
+            token_endpoint_auth_method: metadata.token_endpoint_auth_method,
+        }),
+    ))
+}
+
+struct RegisteredClient {
+    id: Uuid,
+    client_id: Uuid,
+    name: String,
+    redirect_uris: Vec<String>,
+    grant_types: Vec<String>,
+    token_endpoint_auth_method: String,
+    created_at: OffsetDateTime,
+    /// Whether the project still allows dynamic registration.
+    registration_allowed: bool,
+}
+
+impl RegisteredClient {
+    fn into_response(self) -> ClientInformationResponse {
+        let has_secret = self.token_endpoint_auth_method == CLIENT_SECRET_BASIC;
+        ClientInformationResponse {
+            client_id: self.client_id.to_string(),
+            client_secret: None,
+            client_secret_expires_at: has_secret.then_some(0),
+            client_id_issued_at: self.created_at.unix_timestamp(),
+            registration_access_token: None,
+            registration_client_uri: registration_client_uri(self.client_id),
+            client_name: self.name,
+            redirect_uris: self.redirect_uris,
+            grant_types: self.grant_types,
+            token_endpoint_auth_method: self.token_endpoint_auth_method,
+        }
+    }
+}
+
+/// Authenticates a management request (RFC 7592). Unknown clients, clients created by admins
+/// and wrong tokens are all rejected alike.
+///
+/// Once the project opts out of dynamic registration, its registered clients can no longer change
+/// their metadata, but can still read and delete themselves, so a partner can clean up after the
+/// project stops accepting them.
+async fn registered_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    client_id: &str,
+) -> Result<RegisteredClient, AppError> {
+    let token = jwt::get_jwt_token(headers)?;
+    let client_id = id::parse_uuid(&client_id).map_err(|_| AppError::InvalidToken)?;
+
+    let client = sqlx::query!(
+        r#"
+            SELECT a.id, a.client_id, a.name, a.redirect_uris, a.grant_types, a.token_endpoint_auth_method,
+                   a.created_at, a.registration_access_token_hash AS "registration_access_token_hash!",
+                   p.allow_dynamic_registration
+            FROM applications a
+            JOIN projects p ON p.id = a.project_id
+            JOIN organizations o ON o.id = p.org_id
+            WHERE a.client_id = $1 AND a.registration_access_token_hash IS NOT NULL
+              AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        client_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::InvalidToken)?;
+
+    crypto::verify_password(token, &client.registration_access_token_hash).map_err(|_| AppError::InvalidToken)?;
+
+    Ok(RegisteredClient {
+        id: client.id,
+        client_id: client.client_id,
+        name: client.name,
+        redirect_uris: client.redirect_uris,
+        grant_types: client.grant_types,
+        token_endpoint_auth_method: client.token_endpoint_auth_method,
+        created_at: client.created_at,
+        registration_allowed: client.allow_dynamic_registration,
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/clients/{client_id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("client_id" = String, Path, description = "Client ID (UUID v7)")),
+    responses(
+        (status = 200, description = "The client's current metadata", body = ClientInformationResponse),
+        (status = 401, description = "Invalid registration access token, or no such registered client"),
+    )
+)]
+pub async fn get_client_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(ClientPath { client_id }): Path<ClientPath>,
+) -> Result<impl IntoResponse, AppError> {
+    let client = registered_client(&state, &headers, &client_id).await?;
+
+    Ok((StatusCode::OK, Json(client.into_response())))
+}
+
+#[utoipa::path(
+    put,
+    path = "/clients/{client_id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("client_id" = String, Path, description = "Client ID (UUID v7)")),
+    request_body = ClientMetadata,
+    responses(
+        (status = 200, description = "Metadata replaced; omitted fields fall back to their defaults", body = ClientInformationResponse),
+        (status = 400, description = "`invalid_redirect_uri` or `invalid_client_metadata`, including a `client_id` that does not match or a changed `token_endpoint_auth_method`"),
+        (status = 401, description = "Invalid registration access token, or no such registered client"),
+        (status = 403, description = "The project no longer allows dynamic registration"),
+    )
+)]
+pub async fn update_client_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(ClientPath { client_id }): Path<ClientPath>,
+    Json(body): Json<ClientMetadata>,
+) -> Result<impl IntoResponse, AppError> {
+    let client = registered_client(&state, &headers, &client_id).await?;
+    if !client.registration_allowed {
+        return Err(AppError::Forbidden);
+    }
+
+    if body.client_id.as_deref() != Some(client.client_id.to_string().as_str()) {
+        return Err(AppError::OAuth("invalid_client_metadata"));
+    }
+    // Switching to or from a public client would need secrets issued or revoked; register anew instead.
+    let current_method = client.token_endpoint_auth_method.clone();
+    let metadata = validate_metadata(ClientMetadata {
+        token_endpoint_auth_method: body.token_endpoint_auth_method.or(Some(current_method.clone())),
+        ..body
+    })?;
+    if metadata.token_endpoint_auth_method != current_method {
+        return Err(AppError::OAuth("invalid_client_metadata"));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let record = sqlx::query!(
+        r#"
+            UPDATE applications SET name = $2, redirect_uris = $3, grant_types = $4
+            WHERE id = $1
+            RETURNING name
+        "#,
+        client.id,
+        metadata.client_name.unwrap_or_else(|| client.client_id.to_string()),
+        &metadata.redirect_uris,
+        &metadata.grant_types,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "client_update",
+            success: true,
+            route: "/auth/clients/{client_id}",
+            application_id: Some(client.id),
+            application_name: Some(record.name.as_str()),
+            http_status: Some(200),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let client = RegisteredClient {
+        name: record.name,
+        redirect_uris: metadata.redirect_uris,
+        grant_types: metadata.grant_types,
+        ..client
+    };
+
+    Ok((StatusCode::OK, Json(client.into_response())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/clients/{client_id}",
+    tag = "auth",
+    security(("bearer_auth" = [])),
+    params(("client_id" = String, Path, description = "Client ID (UUID v7)")),
+    responses(
+        (status = 204, description = "Client deleted; it stops issuing and accepting tokens at once. Admins can restore it within the restore window"),
+        (status = 401, description = "Invalid registration access token, or no such registered client"),
+    )
+)]
+pub async fn delete_client_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(ClientPath { client_id }): Path<ClientPath>,
+) -> Result<impl IntoResponse, AppError> {
+    let client = registered_client(&state, &headers, &client_id).await?;
+
+    let mut tx = state.pool.begin().await?;
+
+    // Same soft delete as the admin API, so the restore window applies.
+    sqlx::query!(
+        "UPDATE applications SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+        client.id,
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    audit::write_auth_event(
+        &mut *tx,
+        AuthEvent {
+            event_type: "client_deletion",
+            success: true,
+            route: "/auth/clients/{client_id}",
+            application_id: Some(client.id),
+            application_name: Some(client.name.as_str()),
+            http_status: Some(204),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
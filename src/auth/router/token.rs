@@ -0,0 +1,344 @@
+use crate::audit::{self, AuthEvent};
+use crate::auth::authorization::{AccountId, ApplicationClient};
+use crate::auth::clients::{self, DEVICE_CODE_GRANT, TOKEN_EXCHANGE_GRANT};
+use crate::auth::router::device::{self, POLL_INTERVAL_SECS};
+use crate::auth::{consent, scopes};
+use crate::error::{AppError, ValidationErrors};
+use crate::router::AppState;
+use crate::{config, crypto, id, jwt};
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::{Form, Json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use utoipa::ToSchema;
+
+/// Token types accepted for subject and actor tokens, and the one issued.
+const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+const JWT_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:jwt";
+
+#[derive(Deserialize, ToSchema)]
+pub struct TokenRequest {
+    /// `urn:ietf:params:oauth:grant-type:token-exchange` or `urn:ietf:params:oauth:grant-type:device_code`.
+    grant_type: String,
+    /// Device code grant: the `device_code` from `/auth/device/code`.
+    device_code: Option<String>,
+    /// Device code grant: the public client the device code was issued to.
+    client_id: Option<String>,
+    /// A user token issued by this server for the caller's project.
+    subject_token: Option<String>,
+    /// `urn:ietf:params:oauth:token-type:access_token` or `urn:ietf:params:oauth:token-type:jwt`.
+    subject_token_type: Option<String>,
+    /// A user token of the party acting for the subject; it becomes the outermost `act`.
+    actor_token: Option<String>,
+    /// Required with `actor_token`; same values as `subject_token_type`.
+    actor_token_type: Option<String>,
+    /// `client_id` of the application the new token is for: any live application of the subject's
+    /// project. Defaults to the subject token's audience.
+    audience: Option<String>,
+    /// Token exchange: space-delimited permission names. Defaults to everything the ceiling allows.
+    scope: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TokenResponse {
+    access_token: String,
+    /// Set for token exchange only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issued_token_type: Option<&'static str>,
+    token_type: &'static str,
+    /// Seconds until `access_token` expires.
+    expires_in: u64,
+    /// Space-delimited permission names carried by `access_token`.
+    scope: String,
+}
+
+pub(super) fn field_error(field: &str, message: &str) -> AppError {
+    let mut errors = HashMap::new();
+    errors.insert(field.to_string(), vec![message.to_string()]);
+    AppError::ValidationError(ValidationErrors::new(errors))
+}
+
+fn check_token_type(field: &str, token_type: Option<&str>) -> Result<(), AppError> {
+    match token_type {
+        Some(ACCESS_TOKEN_TYPE | JWT_TOKEN_TYPE) => Ok(()),
+        _ => Err(field_error(field, "must be an access_token or jwt token type")),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/token",
+    tag = "auth",
+    security((), ("client_auth" = [])),
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Token issued", body = TokenResponse),
+        (status = 400, description = "Unsupported grant type, an audience outside the subject's project or not approved by the user, a scope beyond what the account holds there, or `unauthorized_client` if the application did not register for the grant, or an OAuth error while polling with a device code (`authorization_pending`, `slow_down`, `access_denied`, `expired_token`, `invalid_grant`)"),
+        (status = 401, description = "Invalid client credentials, subject token or actor token"),
+        (status = 403, description = "The subject token was not issued to the calling application"),
+    )
+)]
+pub async fn token_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(body): Form<TokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    match body.grant_type.as_str() {
+        TOKEN_EXCHANGE_GRANT => {
+            let client = ApplicationClient::from_headers(&state, &headers).await?;
+            exchange_token(&state, &client, body).await
+        }
+        // Devices are public clients: the device code is the only secret they hold.
+        DEVICE_CODE_GRANT => redeem_device_code(&state, body).await,
+        _ => Err(field_error("grant_type", "is not supported")),
+    }
+}
+
+/// Exchanges a user token for one that a service can pass downstream, possibly to another
+/// application of the same project. Only the application the subject token was issued to may
+/// exchange it. The new token's scopes are a subset of what the account holds on the audience,
+/// and of what the user approved when the audience is third-party; for the subject's own audience
+/// they also stay within the subject token's. It expires no later than the subject token.
+async fn exchange_token(
+    state: &AppState,
+    client: &ApplicationClient,
+    body: TokenRequest,
+) -> Result<(StatusCode, Json<TokenResponse>), AppError> {
+    clients::require_grant(&client.grant_types, TOKEN_EXCHANGE_GRANT)?;
+
+    let subject_token = body
+        .subject_token
+        .as_deref()
+        .ok_or_else(|| field_error("subject_token", "is required"))?;
+    check_token_type("subject_token_type", body.subject_token_type.as_deref())?;
+    let (subject, claims) = AccountId::from_token(state, subject_token).await?;
+
+    if subject.project_id != client.project_id || subject.client_id != client.client_id {
+        return Err(AppError::Forbidden);
+    }
+
+    // The actor becomes the current `act`, with the subject token's chain nested below it.
+    let act = match body.actor_token.as_deref() {
+        Some(actor_token) => {
+            check_token_type("actor_token_type", body.actor_token_type.as_deref())?;
+            let (actor, _) = AccountId::from_token(state, actor_token).await?;
+            if actor.project_id != client.project_id {
+                return Err(AppError::Forbidden);
+            }
+            Some(jwt::Actor {
+                sub: actor.account_id.to_string(),
+                act: claims.act.map(Box::new),
+            })
+        }
+        None => claims.act,
+    };
+
+    let audience = match body.audience.as_deref() {
+        Some(audience) => id::parse_uuid(&audience).map_err(|_| field_error("audience", "is not a valid client_id"))?,
+        None => subject.client_id,
+    };
+    let application = sqlx::query!(
+        r#"
+        SELECT a.id, a.name, a.first_party
+        FROM applications a
+        JOIN projects p ON p.id = a.project_id
+        JOIN organizations o ON o.id = p.org_id
+        WHERE a.client_id = $1 AND a.project_id = $2
+          AND a.deleted_at IS NULL AND p.deleted_at IS NULL AND o.deleted_at IS NULL
+        "#,
+        audience,
+        subject.project_id,
+    )
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| field_error("audience", "is not an application of the subject's project"))?;
+
+    let mut ceiling = scopes::for_application(&state.pool, subject.account_id, application.id).await?;
+    if audience == subject.client_id {
+        let granted = claims.scope.unwrap_or_default();
+        let granted: Vec<&str> = granted.split_whitespace().collect();
+        ceiling.retain(|scope| granted.contains(&scope.as_str()));
+    }
+    // A third-party audience gets no more than the user approved for it.
+    if !application.first_party {
+        let approved = consent::approved_scopes(&state.pool, subject.account_id, application.id)
+            .await?
+            .ok_or_else(|| field_error("audience", "has not been approved by the user"))?;
+        ceiling.retain(|scope| approved.contains(scope));
+    }
+
+    let scopes: Vec<String> = match body.scope.as_deref() {
+        Some(requested) => {
+            let requested: Vec<String> = requested.split_whitespace().map(str::to_string).collect();
+            if let Some(extra) = requested.iter().find(|scope| !ceiling.contains(scope)) {
+                return Err(field_error(
+                    "scope",
+                    &format!("'{extra}' exceeds what the account holds on the audience"),
+                ));
+            }
+            requested
+        }
+        None => ceiling,
+    };
+
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+    let remaining = Duration::from_secs(claims.exp.saturating_sub(now.as_secs()));
+    let duration = remaining.min(Duration::from_mins(
+        config::env::env().user_access_token_duration_in_minutes as u64,
+    ));
+
+    let access_token = jwt::generate_exchanged_token(
+        &jwt::UserTokenSubject {
+            account_id: &subject.account_id.to_string(),
+            project_id: &subject.project_id.to_string(),
+            client_id: &audience.to_string(),
+            scopes: &scopes,
+        },
+        act,
+        duration,
+    )?;
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "token_exchange",
+            success: true,
+            route: "/auth/token",
+            user_account_id: Some(subject.account_id),
+            application_id: Some(application.id),
+            application_name: Some(application.name.as_str()),
+            http_status: Some(200),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(TokenResponse {
+            access_token,
+            issued_token_type: Some(ACCESS_TOKEN_TYPE),
+            token_type: "Bearer",
+            expires_in: duration.as_secs(),
+            scope: scopes.join(" "),
+        }),
+    ))
+}
+
+/// Answers a device's poll. Until the user has decided, the device is told to keep waiting, and
+/// each poll that comes sooner than the interval allows makes the interval longer. An approved
+/// request is redeemed exactly once.
+async fn redeem_device_code(
+    state: &AppState,
+    body: TokenRequest,
+) -> Result<(StatusCode, Json<TokenResponse>), AppError> {
+    let device_code = body
+        .device_code
+        .as_deref()
+        .ok_or_else(|| field_error("device_code", "is required"))?;
+    let client_id = body
+        .client_id
+        .as_deref()
+        .ok_or_else(|| field_error("client_id", "is required"))?;
+    let client_id = id::parse_uuid(&client_id).map_err(|_| AppError::OAuth("invalid_grant"))?;
+    let (device_id, secret) = device::parse_device_code(device_code).ok_or(AppError::OAuth("invalid_grant"))?;
+
+    let authorization = sqlx::query!(
+        r#"
+        SELECT da.device_code_hash, da.status, da.scopes, da.poll_interval_secs, da.application_id,
+            a.name AS application_name, a.project_id, a.grant_types,
+            da.expires_at <= NOW() AS "expired!",
+            COALESCE(da.last_polled_at + make_interval(secs => da.poll_interval_secs) > NOW(), false) AS "too_soon!"
+        FROM device_authorizations da
+        JOIN applications a ON a.id = da.application_id
+        WHERE da.id = $1 AND a.client_id = $2 AND a.deleted_at IS NULL
+        "#,
+        device_id,
+        client_id
+    )
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::OAuth("invalid_grant"))?;
+
+    if crypto::verify_password(secret, &authorization.device_code_hash).is_err() {
+        return Err(AppError::OAuth("invalid_grant"));
+    }
+    clients::require_grant(&authorization.grant_types, DEVICE_CODE_GRANT)?;
+    if authorization.expired {
+        return Err(AppError::OAuth("expired_token"));
+    }
+    if authorization.too_soon {
+        sqlx::query!(
+            "UPDATE device_authorizations SET poll_interval_secs = poll_interval_secs + $2, last_polled_at = NOW() WHERE id = $1",
+            device_id,
+            POLL_INTERVAL_SECS
+        )
+        .execute(&state.pool)
+        .await?;
+        return Err(AppError::OAuth("slow_down"));
+    }
+    sqlx::query!(
+        "UPDATE device_authorizations SET last_polled_at = NOW() WHERE id = $1",
+        device_id
+    )
+    .execute(&state.pool)
+    .await?;
+
+    match authorization.status.as_str() {
+        "pending" => return Err(AppError::OAuth("authorization_pending")),
+        "denied" => return Err(AppError::OAuth("access_denied")),
+        "approved" => {}
+        _ => return Err(AppError::OAuth("invalid_grant")),
+    }
+
+    // Only one of several racing polls redeems the approval.
+    let account_id = sqlx::query_scalar!(
+        r#"UPDATE device_authorizations SET status = 'redeemed' WHERE id = $1 AND status = 'approved' RETURNING user_account_id AS "user_account_id!""#,
+        device_id
+    )
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::OAuth("invalid_grant"))?;
+
+    // Grants may have changed since the request was made; never issue more than the account holds now.
+    let mut scopes = scopes::for_application(&state.pool, account_id, authorization.application_id).await?;
+    if let Some(requested) = &authorization.scopes {
+        scopes.retain(|scope| requested.contains(scope));
+    }
+
+    let access_token = jwt::generate_user_token(&jwt::UserTokenSubject {
+        account_id: &account_id.to_string(),
+        project_id: &authorization.project_id.to_string(),
+        client_id: &client_id.to_string(),
+        scopes: &scopes,
+    })?;
+
+    audit::write_auth_event(
+        &state.pool,
+        AuthEvent {
+            event_type: "device_token",
+            success: true,
+            route: "/auth/token",
+            user_account_id: Some(account_id),
+            application_id: Some(authorization.application_id),
+            application_name: Some(authorization.application_name.as_str()),
+            http_status: Some(200),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(TokenResponse {
+            access_token,
+            issued_token_type: None,
+            token_type: "Bearer",
+            expires_in: config::env::env().user_access_token_duration_in_minutes as u64 * 60,
+            scope: scopes.join(" "),
+        }),
+    ))
+}
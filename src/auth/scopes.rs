@@ -0,0 +1,60 @@
+use crate::error::AppError;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+// Effective permissions are the union of direct `account_scopes` grants and the permissions
+// bundled by the `project_roles` assigned to the account through `account_roles`.
+
+/// Permission names granted to the account on one application, as carried in the token `scope` claim.
+pub async fn for_application(
+    executor: impl PgExecutor<'_>,
+    account_id: Uuid,
+    app_id: Uuid,
+) -> Result<Vec<String>, AppError> {
+    let scopes = sqlx::query_scalar!(
+        r#"
+        SELECT p.name
+        FROM permissions p
+        WHERE p.app_id = $2
+          AND p.id IN (
+              SELECT permission_id FROM account_scopes WHERE account_id = $1
+              UNION
+              SELECT rp.permission_id
+              FROM account_roles ar
+              JOIN project_role_permissions rp ON rp.role_id = ar.role_id
+              WHERE ar.account_id = $1
+          )
+        ORDER BY p.name
+        "#,
+        account_id,
+        app_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(scopes)
+}
+
+/// Permission names granted to the account across every application of its project.
+pub async fn for_account(executor: impl PgExecutor<'_>, account_id: Uuid) -> Result<Vec<String>, AppError> {
+    let scopes = sqlx::query_scalar!(
+        r#"
+        SELECT DISTINCT p.name
+        FROM permissions p
+        WHERE p.id IN (
+            SELECT permission_id FROM account_scopes WHERE account_id = $1
+            UNION
+            SELECT rp.permission_id
+            FROM account_roles ar
+            JOIN project_role_permissions rp ON rp.role_id = ar.role_id
+            WHERE ar.account_id = $1
+        )
+        ORDER BY p.name
+        "#,
+        account_id
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(scopes)
+}
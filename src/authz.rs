@@ -0,0 +1,107 @@
+use crate::error::AppError;
+use serde::Serialize;
+use sqlx::PgExecutor;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+pub mod relations;
+pub mod router;
+
+/// How long a relying service may cache a decision derived from the account's grants.
+/// Kept short so revoked scopes and roles stop applying quickly.
+pub const DECISION_CACHE_TTL_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionReason {
+    /// The permission is granted to the account through `account_scopes`.
+    DirectGrant,
+    /// The permission is bundled by one of the account's roles.
+    RoleGrant,
+    /// The permission exists on the application but the account does not hold it.
+    NotGranted,
+    /// The application defines no permission with this name.
+    UnknownPermission,
+    /// No such account in the application's project.
+    AccountNotFound,
+    /// The account's identity is disabled.
+    AccountInactive,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Decision {
+    pub allowed: bool,
+    pub reason: DecisionReason,
+    /// Seconds the decision may be cached for. Absent when the decision should not be cached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+}
+
+impl Decision {
+    fn from_reason(reason: DecisionReason) -> Self {
+        let allowed = matches!(reason, DecisionReason::DirectGrant | DecisionReason::RoleGrant);
+        let cache_ttl = match reason {
+            DecisionReason::DirectGrant | DecisionReason::RoleGrant | DecisionReason::NotGranted => {
+                Some(DECISION_CACHE_TTL_SECS)
+            }
+            _ => None,
+        };
+
+        Decision {
+            allowed,
+            reason,
+            cache_ttl,
+        }
+    }
+}
+
+/// Decides whether the account holds `permission` on the application, evaluating direct
+/// grants and roles the same way tokens are issued (see `auth::scopes`).
+pub async fn check(
+    executor: impl PgExecutor<'_>,
+    app_id: Uuid,
+    project_id: Uuid,
+    account_id: Uuid,
+    permission: &str,
+) -> Result<Decision, AppError> {
+    let record = sqlx::query!(
+        r#"
+        SELECT
+            (SELECT i.is_active
+             FROM user_accounts ua
+             JOIN identities i ON i.id = ua.identity_id
+             WHERE ua.id = $1 AND ua.project_id = $2) AS "account_active?",
+            EXISTS(SELECT 1 FROM permissions WHERE app_id = $3 AND name = $4) AS "permission_exists!",
+            EXISTS(
+                SELECT 1
+                FROM account_scopes s
+                JOIN permissions p ON p.id = s.permission_id
+                WHERE s.account_id = $1 AND p.app_id = $3 AND p.name = $4
+            ) AS "direct!",
+            EXISTS(
+                SELECT 1
+                FROM account_roles ar
+                JOIN project_role_permissions rp ON rp.role_id = ar.role_id
+                JOIN permissions p ON p.id = rp.permission_id
+                WHERE ar.account_id = $1 AND p.app_id = $3 AND p.name = $4
+            ) AS "via_role!"
+        "#,
+        account_id,
+        project_id,
+        app_id,
+        permission
+    )
+    .fetch_one(executor)
+    .await?;
+
+    let reason = match record.account_active {
+        None => DecisionReason::AccountNotFound,
+        Some(false) => DecisionReason::AccountInactive,
+        Some(true) if !record.permission_exists => DecisionReason::UnknownPermission,
+        Some(true) if record.direct => DecisionReason::DirectGrant,
+        Some(true) if record.via_role => DecisionReason::RoleGrant,
+        Some(true) => DecisionReason::NotGranted,
+    };
+
+    Ok(Decision::from_reason(reason))
+}
@@ -0,0 +1,597 @@
+//! Relationship-based authorization: a per-project store of `object#relation@subject` tuples,
+//! evaluated through namespace configurations with userset rewrites.
+//!
+//! Every tuple write bumps `projects.authz_revision`. Tuples record the revision they were written
+//! and deleted at, so one evaluation reads a single revision even while writes go on, and the
+//! revision is handed back to callers as an opaque consistency token.
+
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Deepest chain of rewrites and userset hops followed by a single check.
+const MAX_DEPTH: u8 = 32;
+
+/// Upper bound on candidate objects considered by `list_objects`.
+pub const MAX_LIST_OBJECTS: i64 = 1000;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+fn invalid(field: &str, message: String) -> AppError {
+    let mut errors = HashMap::new();
+    errors.insert(field.to_string(), vec![message]);
+    AppError::ValidationError(ValidationErrors::new(errors))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains([':', '#', '@']) && !name.chars().any(char::is_whitespace)
+}
+
+/// `namespace:id`, e.g. `doc:42`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectRef {
+    pub namespace: String,
+    pub id: String,
+}
+
+impl ObjectRef {
+    pub fn parse(field: &str, value: &str) -> Result<Self, AppError> {
+        match value.split_once(':') {
+            Some((namespace, id)) if is_valid_name(namespace) && !id.is_empty() && !id.contains(['#', '@']) => {
+                Ok(ObjectRef {
+                    namespace: namespace.to_string(),
+                    id: id.to_string(),
+                })
+            }
+            _ => Err(invalid(field, format!("'{value}' is not a 'namespace:id' object"))),
+        }
+    }
+}
+
+impl fmt::Display for ObjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.id)
+    }
+}
+
+/// Either a direct subject `user:alice` or a userset `team:a#member`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubjectRef {
+    pub object: ObjectRef,
+    pub relation: Option<String>,
+}
+
+impl SubjectRef {
+    pub fn parse(field: &str, value: &str) -> Result<Self, AppError> {
+        let (object, relation) = match value.split_once('#') {
+            Some((object, relation)) if is_valid_name(relation) => (object, Some(relation.to_string())),
+            Some(_) => return Err(invalid(field, format!("'{value}' has an invalid userset relation"))),
+            None => (value, None),
+        };
+
+        Ok(SubjectRef {
+            object: ObjectRef::parse(field, object)?,
+            relation,
+        })
+    }
+}
+
+impl fmt::Display for SubjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.relation {
+            Some(relation) => write!(f, "{}#{}", self.object, relation),
+            None => write!(f, "{}", self.object),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tuple {
+    pub object: ObjectRef,
+    pub relation: String,
+    pub subject: SubjectRef,
+}
+
+/// How a relation's subjects are computed. Relations without a rewrite only hold their own tuples.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rewrite {
+    /// Subjects written directly on this object and relation.
+    #[default]
+    This,
+    /// Subjects of another relation on the same object.
+    ComputedUserset {
+        relation: String,
+    },
+    /// Follows `tupleset` to other objects and takes their `computed_userset` relation,
+    /// e.g. viewers of a doc's parent folder.
+    TupleToUserset {
+        tupleset: String,
+        computed_userset: String,
+    },
+    Union(Vec<Rewrite>),
+    Intersection(Vec<Rewrite>),
+    Exclusion {
+        base: Box<Rewrite>,
+        subtract: Box<Rewrite>,
+    },
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RelationConfig {
+    #[serde(default)]
+    pub rewrite: Rewrite,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct NamespaceConfig {
+    /// Relation name to its configuration; `{}` declares a relation holding only direct tuples.
+    #[schema(value_type = Object)]
+    pub relations: BTreeMap<String, RelationConfig>,
+}
+
+impl NamespaceConfig {
+    /// Checks relation names and that rewrites only refer to relations declared in this namespace.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.relations.is_empty() {
+            return Err(invalid("relations", "must not be empty".to_string()));
+        }
+
+        let mut errors: Vec<String> = Vec::new();
+        for (name, config) in &self.relations {
+            if !is_valid_name(name) {
+                errors.push(format!("'{name}' is not a valid relation name"));
+            }
+            self.collect_unknown(name, &config.rewrite, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            let mut map = HashMap::new();
+            map.insert("relations".to_string(), errors);
+            Err(AppError::ValidationError(ValidationErrors::new(map)))
+        }
+    }
+
+    fn collect_unknown(&self, name: &str, rewrite: &Rewrite, errors: &mut Vec<String>) {
+        match rewrite {
+            Rewrite::This => {}
+            Rewrite::ComputedUserset { relation } | Rewrite::TupleToUserset { tupleset: relation, .. } => {
+                if !self.relations.contains_key(relation) {
+                    errors.push(format!("'{name}' refers to unknown relation '{relation}'"));
+                }
+            }
+            Rewrite::Union(children) | Rewrite::Intersection(children) => {
+                children
+                    .iter()
+                    .for_each(|child| self.collect_unknown(name, child, errors));
+            }
+            Rewrite::Exclusion { base, subtract } => {
+                self.collect_unknown(name, base, errors);
+                self.collect_unknown(name, subtract, errors);
+            }
+        }
+    }
+}
+
+/// Result of `expand`: the rewrite tree of a relation with subjects and usersets as leaves.
+/// Userset leaves (`team:a#member`) are not expanded further; callers expand them on demand.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExpandNode {
+    Leaf {
+        subjects: Vec<String>,
+    },
+    Union {
+        children: Vec<ExpandNode>,
+    },
+    Intersection {
+        children: Vec<ExpandNode>,
+    },
+    Exclusion {
+        base: Box<ExpandNode>,
+        subtract: Box<ExpandNode>,
+    },
+}
+
+pub fn encode_consistency_token(revision: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("rev:{revision}"))
+}
+
+pub fn decode_consistency_token(token: &str) -> Result<i64, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|value| value.strip_prefix("rev:").and_then(|rev| rev.parse().ok()))
+        .ok_or_else(|| invalid("consistency_token", "is not a valid consistency token".to_string()))
+}
+
+async fn load_namespaces(pool: &PgPool, project_id: Uuid) -> Result<HashMap<String, NamespaceConfig>, AppError> {
+    let records = sqlx::query!(
+        "SELECT name, config FROM relation_namespaces WHERE project_id = $1",
+        project_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    records
+        .into_iter()
+        .map(|r| {
+            let config = serde_json::from_value(r.config)
+                .map_err(|_| invalid("namespace", format!("stored configuration of '{}' is invalid", r.name)))?;
+            Ok((r.name, config))
+        })
+        .collect()
+}
+
+fn relation_rewrite<'n>(
+    namespaces: &'n HashMap<String, NamespaceConfig>,
+    object: &ObjectRef,
+    relation: &str,
+) -> Result<&'n Rewrite, AppError> {
+    let namespace = namespaces
+        .get(&object.namespace)
+        .ok_or_else(|| invalid("namespace", format!("unknown namespace '{}'", object.namespace)))?;
+
+    namespace
+        .relations
+        .get(relation)
+        .map(|config| &config.rewrite)
+        .ok_or_else(|| {
+            invalid(
+                "relation",
+                format!("unknown relation '{}#{relation}'", object.namespace),
+            )
+        })
+}
+
+/// Applies deletes then writes in one revision and returns that revision.
+/// Object relations must be declared by their namespace; writing an existing tuple is a no-op.
+pub async fn write_tuples(
+    pool: &PgPool,
+    project_id: Uuid,
+    writes: &[Tuple],
+    deletes: &[Tuple],
+) -> Result<i64, AppError> {
+    let namespaces = load_namespaces(pool, project_id).await?;
+    for tuple in writes {
+        relation_rewrite(&namespaces, &tuple.object, &tuple.relation)?;
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Bumping the counter also serializes concurrent writers of the same project.
+    let revision = sqlx::query_scalar!(
+        "UPDATE projects SET authz_revision = authz_revision + 1 WHERE id = $1 RETURNING authz_revision",
+        project_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    for tuple in deletes {
+        sqlx::query!(
+            r#"
+            UPDATE relation_tuples SET deleted_revision = $1, deleted_at = NOW()
+            WHERE project_id = $2 AND namespace = $3 AND object_id = $4 AND relation = $5
+              AND subject_namespace = $6 AND subject_id = $7 AND subject_relation = $8
+              AND deleted_revision IS NULL
+            "#,
+            revision,
+            project_id,
+            tuple.object.namespace,
+            tuple.object.id,
+            tuple.relation,
+            tuple.subject.object.namespace,
+            tuple.subject.object.id,
+            tuple.subject.relation.as_deref().unwrap_or(""),
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for tuple in writes {
+        sqlx::query!(
+            r#"
+            INSERT INTO relation_tuples
+                (id, project_id, namespace, object_id, relation, subject_namespace, subject_id, subject_relation, created_revision)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (project_id, namespace, object_id, relation, subject_namespace, subject_id, subject_relation)
+                WHERE deleted_revision IS NULL
+            DO NOTHING
+            "#,
+            id::new_uuid(),
+            project_id,
+            tuple.object.namespace,
+            tuple.object.id,
+            tuple.relation,
+            tuple.subject.object.namespace,
+            tuple.subject.object.id,
+            tuple.subject.relation.as_deref().unwrap_or(""),
+            revision,
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(revision)
+}
+
+/// A read-only view of a project's tuples at one revision.
+pub struct Snapshot<'a> {
+    pool: &'a PgPool,
+    project_id: Uuid,
+    pub revision: i64,
+    namespaces: HashMap<String, NamespaceConfig>,
+}
+
+impl<'a> Snapshot<'a> {
+    /// Opens the project's latest revision, which must be at least as fresh as `consistency_token`.
+    pub async fn open(pool: &'a PgPool, project_id: Uuid, consistency_token: Option<&str>) -> Result<Self, AppError> {
+        let revision = sqlx::query_scalar!("SELECT authz_revision FROM projects WHERE id = $1", project_id)
+            .fetch_one(pool)
+            .await?;
+
+        if let Some(token) = consistency_token
+            && decode_consistency_token(token)? > revision
+        {
+            return Err(invalid(
+                "consistency_token",
+                "was not issued for this project".to_string(),
+            ));
+        }
+
+        Ok(Snapshot {
+            pool,
+            project_id,
+            revision,
+            namespaces: load_namespaces(pool, project_id).await?,
+        })
+    }
+
+    pub fn consistency_token(&self) -> String {
+        encode_consistency_token(self.revision)
+    }
+
+    async fn subjects(&self, object: &ObjectRef, relation: &str) -> Result<Vec<SubjectRef>, AppError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT subject_namespace, subject_id, subject_relation
+            FROM relation_tuples
+            WHERE project_id = $1 AND namespace = $2 AND object_id = $3 AND relation = $4
+              AND created_revision <= $5 AND (deleted_revision IS NULL OR deleted_revision > $5)
+            ORDER BY subject_namespace, subject_id, subject_relation
+            "#,
+            self.project_id,
+            object.namespace,
+            object.id,
+            relation,
+            self.revision,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|r| SubjectRef {
+                object: ObjectRef {
+                    namespace: r.subject_namespace,
+                    id: r.subject_id,
+                },
+                relation: (!r.subject_relation.is_empty()).then_some(r.subject_relation),
+            })
+            .collect())
+    }
+
+    /// Whether `subject` holds `relation` on `object`.
+    pub async fn check(&self, object: &ObjectRef, relation: &str, subject: &SubjectRef) -> Result<bool, AppError> {
+        self.check_relation(object, relation, subject, 0).await
+    }
+
+    fn check_relation<'s>(
+        &'s self,
+        object: &'s ObjectRef,
+        relation: &'s str,
+        subject: &'s SubjectRef,
+        depth: u8,
+    ) -> BoxFuture<'s, Result<bool, AppError>> {
+        Box::pin(async move {
+            if depth > MAX_DEPTH {
+                return Err(invalid(
+                    "relation",
+                    "relation graph is too deep to evaluate".to_string(),
+                ));
+            }
+
+            let rewrite = relation_rewrite(&self.namespaces, object, relation)?;
+            self.check_rewrite(object, relation, rewrite, subject, depth).await
+        })
+    }
+
+    fn check_rewrite<'s>(
+        &'s self,
+        object: &'s ObjectRef,
+        relation: &'s str,
+        rewrite: &'s Rewrite,
+        subject: &'s SubjectRef,
+        depth: u8,
+    ) -> BoxFuture<'s, Result<bool, AppError>> {
+        Box::pin(async move {
+            match rewrite {
+                Rewrite::This => {
+                    for stored in self.subjects(object, relation).await? {
+                        if &stored == subject {
+                            return Ok(true);
+                        }
+                        if let Some(userset) = &stored.relation
+                            && self.check_relation(&stored.object, userset, subject, depth + 1).await?
+                        {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                Rewrite::ComputedUserset { relation } => {
+                    self.check_relation(object, relation, subject, depth + 1).await
+                }
+                Rewrite::TupleToUserset {
+                    tupleset,
+                    computed_userset,
+                } => {
+                    for stored in self.subjects(object, tupleset).await? {
+                        if self
+                            .check_relation(&stored.object, computed_userset, subject, depth + 1)
+                            .await?
+                        {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                Rewrite::Union(children) => {
+                    for child in children {
+                        if self.check_rewrite(object, relation, child, subject, depth + 1).await? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                Rewrite::Intersection(children) => {
+                    for child in children {
+                        if !self.check_rewrite(object, relation, child, subject, depth + 1).await? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(!children.is_empty())
+                }
+                Rewrite::Exclusion { base, subtract } => {
+                    Ok(self.check_rewrite(object, relation, base, subject, depth + 1).await?
+                        && !self
+                            .check_rewrite(object, relation, subtract, subject, depth + 1)
+                            .await?)
+                }
+            }
+        })
+    }
+
+    /// The rewrite tree of `relation` on `object`, one level deep.
+    pub async fn expand(&self, object: &ObjectRef, relation: &str) -> Result<ExpandNode, AppError> {
+        let rewrite = relation_rewrite(&self.namespaces, object, relation)?;
+        self.expand_rewrite(object, relation, rewrite).await
+    }
+
+    fn expand_rewrite<'s>(
+        &'s self,
+        object: &'s ObjectRef,
+        relation: &'s str,
+        rewrite: &'s Rewrite,
+    ) -> BoxFuture<'s, Result<ExpandNode, AppError>> {
+        Box::pin(async move {
+            let node = match rewrite {
+                Rewrite::This => ExpandNode::Leaf {
+                    subjects: self
+                        .subjects(object, relation)
+                        .await?
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect(),
+                },
+                Rewrite::ComputedUserset { relation } => ExpandNode::Leaf {
+                    subjects: vec![format!("{object}#{relation}")],
+                },
+                Rewrite::TupleToUserset {
+                    tupleset,
+                    computed_userset,
+                } => ExpandNode::Leaf {
+                    subjects: self
+                        .subjects(object, tupleset)
+                        .await?
+                        .iter()
+                        .map(|stored| format!("{}#{computed_userset}", stored.object))
+                        .collect(),
+                },
+                Rewrite::Union(children) => ExpandNode::Union {
+                    children: self.expand_children(object, relation, children).await?,
+                },
+                Rewrite::Intersection(children) => ExpandNode::Intersection {
+                    children: self.expand_children(object, relation, children).await?,
+                },
+                Rewrite::Exclusion { base, subtract } => ExpandNode::Exclusion {
+                    base: Box::new(self.expand_rewrite(object, relation, base).await?),
+                    subtract: Box::new(self.expand_rewrite(object, relation, subtract).await?),
+                },
+            };
+
+            Ok(node)
+        })
+    }
+
+    async fn expand_children(
+        &self,
+        object: &ObjectRef,
+        relation: &str,
+        children: &[Rewrite],
+    ) -> Result<Vec<ExpandNode>, AppError> {
+        let mut nodes = Vec::with_capacity(children.len());
+        for child in children {
+            nodes.push(self.expand_rewrite(object, relation, child).await?);
+        }
+        Ok(nodes)
+    }
+
+    /// Object ids of `namespace` on which `subject` holds `relation`. Candidates are the objects
+    /// that appear in at least one tuple, capped at `MAX_LIST_OBJECTS`.
+    pub async fn list_objects(
+        &self,
+        namespace: &str,
+        relation: &str,
+        subject: &SubjectRef,
+    ) -> Result<Vec<String>, AppError> {
+        let object = ObjectRef {
+            namespace: namespace.to_string(),
+            id: String::new(),
+        };
+        relation_rewrite(&self.namespaces, &object, relation)?;
+
+        let candidates = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT object_id
+            FROM relation_tuples
+            WHERE project_id = $1 AND namespace = $2
+              AND created_revision <= $3 AND (deleted_revision IS NULL OR deleted_revision > $3)
+            ORDER BY object_id
+            LIMIT $4
+            "#,
+            self.project_id,
+            namespace,
+            self.revision,
+            MAX_LIST_OBJECTS,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        let mut objects = Vec::new();
+        for object_id in candidates {
+            let object = ObjectRef {
+                namespace: namespace.to_string(),
+                id: object_id,
+            };
+            if self.check(&object, relation, subject).await? {
+                objects.push(object.id);
+            }
+        }
+
+        Ok(objects)
+    }
+}
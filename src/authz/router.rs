@@ -0,0 +1,120 @@
+use crate::auth::authorization::ApplicationClient;
+use crate::authz::{self, Decision};
+use crate::error::{AppError, ValidationErrors};
+use crate::id;
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+mod relations;
+
+/// Upper bound on checks evaluated by a single batch request.
+const MAX_BATCH_CHECKS: usize = 100;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CheckRequestBody {
+    /// User account ID (UUID v7) in the application's project.
+    account_id: String,
+    /// Permission name defined on the calling application, e.g. `orders:write`.
+    permission: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct BatchCheckRequestBody {
+    checks: Vec<CheckRequestBody>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BatchCheckResponse {
+    /// Decisions in the same order as the requested checks.
+    results: Vec<Decision>,
+}
+
+pub fn get_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(check_handler))
+        .routes(routes!(batch_check_handler))
+        // Relationship tuples
+        .routes(routes!(relations::write_tuples_handler, relations::list_tuples_handler))
+        .routes(routes!(relations::check_relation_handler))
+        .routes(routes!(relations::expand_handler))
+        .routes(routes!(relations::list_objects_handler))
+}
+
+async fn evaluate(
+    state: &AppState,
+    client: &ApplicationClient,
+    check: &CheckRequestBody,
+) -> Result<Decision, AppError> {
+    let account_id = id::parse_uuid(&check.account_id)?;
+    authz::check(
+        &state.pool,
+        client.app_id,
+        client.project_id,
+        account_id,
+        &check.permission,
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/check",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = CheckRequestBody,
+    responses(
+        (status = 200, description = "Decision for the account on the calling application", body = Decision),
+        (status = 400, description = "Invalid account ID"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+async fn check_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<CheckRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let decision = evaluate(&state, &client, &body).await?;
+    Ok((StatusCode::OK, Json(decision)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/check/batch",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = BatchCheckRequestBody,
+    responses(
+        (status = 200, description = "Decisions in request order", body = BatchCheckResponse),
+        (status = 400, description = "Empty or oversized batch, or invalid account ID"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+async fn batch_check_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<BatchCheckRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    if body.checks.is_empty() || body.checks.len() > MAX_BATCH_CHECKS {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "checks".to_string(),
+            vec![format!("Should have from 1 to {MAX_BATCH_CHECKS} checks")],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let mut results = Vec::with_capacity(body.checks.len());
+    for check in &body.checks {
+        results.push(evaluate(&state, &client, check).await?);
+    }
+
+    Ok((StatusCode::OK, Json(BatchCheckResponse { results })))
+}
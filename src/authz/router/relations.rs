@@ -0,0 +1,350 @@
+use crate::auth::authorization::ApplicationClient;
+use crate::authz::relations::{self, ExpandNode, ObjectRef, Snapshot, SubjectRef, Tuple};
+use crate::error::{AppError, ValidationErrors};
+use crate::pagination::{self, CursorPage, CursorParams};
+use crate::router::AppState;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+
+/// Upper bound on writes plus deletes in a single tuple write request.
+const MAX_TUPLE_CHANGES: usize = 100;
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct TupleItem {
+    /// Object as `namespace:id`, e.g. `doc:42`.
+    object: String,
+    relation: String,
+    /// Direct subject `user:alice` or userset `team:a#member`.
+    subject: String,
+}
+
+impl TupleItem {
+    fn parse(&self) -> Result<Tuple, AppError> {
+        Ok(Tuple {
+            object: ObjectRef::parse("object", &self.object)?,
+            relation: self.relation.clone(),
+            subject: SubjectRef::parse("subject", &self.subject)?,
+        })
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WriteTuplesRequestBody {
+    #[serde(default)]
+    writes: Vec<TupleItem>,
+    #[serde(default)]
+    deletes: Vec<TupleItem>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ConsistencyResponse {
+    /// Pass back in later reads to evaluate at a revision at least this fresh.
+    consistency_token: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct TupleFilterParams {
+    namespace: String,
+    object_id: Option<String>,
+    relation: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TupleListItem {
+    #[serde(skip)]
+    id: uuid::Uuid,
+    object: String,
+    relation: String,
+    subject: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: time::OffsetDateTime,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CheckRelationRequestBody {
+    object: String,
+    relation: String,
+    subject: String,
+    consistency_token: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CheckRelationResponse {
+    allowed: bool,
+    consistency_token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ExpandRequestBody {
+    object: String,
+    relation: String,
+    consistency_token: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ExpandResponse {
+    /// Rewrite tree; leaves list subjects and usersets (`team:a#member`) that may be expanded in turn.
+    #[schema(value_type = Object)]
+    tree: ExpandNode,
+    consistency_token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ListObjectsRequestBody {
+    namespace: String,
+    relation: String,
+    subject: String,
+    consistency_token: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ListObjectsResponse {
+    /// Object ids within the namespace.
+    objects: Vec<String>,
+    consistency_token: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/relations/tuples",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = WriteTuplesRequestBody,
+    responses(
+        (status = 200, description = "Deletes then writes applied in one revision", body = ConsistencyResponse),
+        (status = 400, description = "Malformed tuple, unknown namespace or relation, or empty/oversized request"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+pub async fn write_tuples_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<WriteTuplesRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let changes = body.writes.len() + body.deletes.len();
+    if changes == 0 || changes > MAX_TUPLE_CHANGES {
+        let mut errors = HashMap::new();
+        errors.insert(
+            "writes".to_string(),
+            vec![format!(
+                "Should have from 1 to {MAX_TUPLE_CHANGES} writes and deletes in total"
+            )],
+        );
+        return Err(AppError::ValidationError(ValidationErrors::new(errors)));
+    }
+
+    let writes = body
+        .writes
+        .iter()
+        .map(TupleItem::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    let deletes = body
+        .deletes
+        .iter()
+        .map(TupleItem::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let revision = relations::write_tuples(&state.pool, client.project_id, &writes, &deletes).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ConsistencyResponse {
+            consistency_token: relations::encode_consistency_token(revision),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/relations/tuples",
+    tag = "authz",
+    security(("client_auth" = [])),
+    params(TupleFilterParams, CursorParams),
+    responses(
+        (status = 200, description = "Live tuples of the namespace, newest first", body = CursorPage<TupleListItem>),
+        (status = 400, description = "Invalid cursor"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+pub async fn list_tuples_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Query(filter): Query<TupleFilterParams>,
+    Query(params): Query<CursorParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let limit = params.limit();
+
+    let items: Vec<TupleListItem> = if let Some(ref cursor) = params.cursor {
+        let (cursor_time, cursor_id) = pagination::decode_cursor(cursor)?;
+        sqlx::query!(
+            r#"
+                SELECT id, namespace, object_id, relation, subject_namespace, subject_id, subject_relation, created_at
+                FROM relation_tuples
+                WHERE project_id = $1 AND namespace = $2 AND deleted_revision IS NULL
+                  AND ($3::text IS NULL OR object_id = $3)
+                  AND ($4::text IS NULL OR relation = $4)
+                  AND (created_at, id) < ($5, $6)
+                ORDER BY created_at DESC, id DESC
+                LIMIT $7
+            "#,
+            client.project_id,
+            filter.namespace,
+            filter.object_id,
+            filter.relation,
+            cursor_time,
+            cursor_id,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| TupleListItem {
+            id: row.id,
+            object: format!("{}:{}", row.namespace, row.object_id),
+            relation: row.relation,
+            subject: subject_string(&row.subject_namespace, &row.subject_id, &row.subject_relation),
+            created_at: row.created_at,
+        })
+        .collect()
+    } else {
+        sqlx::query!(
+            r#"
+                SELECT id, namespace, object_id, relation, subject_namespace, subject_id, subject_relation, created_at
+                FROM relation_tuples
+                WHERE project_id = $1 AND namespace = $2 AND deleted_revision IS NULL
+                  AND ($3::text IS NULL OR object_id = $3)
+                  AND ($4::text IS NULL OR relation = $4)
+                ORDER BY created_at DESC, id DESC
+                LIMIT $5
+            "#,
+            client.project_id,
+            filter.namespace,
+            filter.object_id,
+            filter.relation,
+            limit + 1,
+        )
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|row| TupleListItem {
+            id: row.id,
+            object: format!("{}:{}", row.namespace, row.object_id),
+            relation: row.relation,
+            subject: subject_string(&row.subject_namespace, &row.subject_id, &row.subject_relation),
+            created_at: row.created_at,
+        })
+        .collect()
+    };
+
+    let page = CursorPage::from_rows(items, limit, |item| pagination::encode_cursor(item.created_at, item.id));
+
+    Ok((StatusCode::OK, Json(page)))
+}
+
+fn subject_string(namespace: &str, id: &str, relation: &str) -> String {
+    if relation.is_empty() {
+        format!("{namespace}:{id}")
+    } else {
+        format!("{namespace}:{id}#{relation}")
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/relations/check",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = CheckRelationRequestBody,
+    responses(
+        (status = 200, description = "Whether the subject holds the relation on the object", body = CheckRelationResponse),
+        (status = 400, description = "Malformed object or subject, unknown namespace or relation, or invalid consistency token"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+pub async fn check_relation_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<CheckRelationRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let object = ObjectRef::parse("object", &body.object)?;
+    let subject = SubjectRef::parse("subject", &body.subject)?;
+
+    let snapshot = Snapshot::open(&state.pool, client.project_id, body.consistency_token.as_deref()).await?;
+    let allowed = snapshot.check(&object, &body.relation, &subject).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(CheckRelationResponse {
+            allowed,
+            consistency_token: snapshot.consistency_token(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/relations/expand",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = ExpandRequestBody,
+    responses(
+        (status = 200, description = "Rewrite tree of the relation on the object", body = ExpandResponse),
+        (status = 400, description = "Malformed object, unknown namespace or relation, or invalid consistency token"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+pub async fn expand_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<ExpandRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let object = ObjectRef::parse("object", &body.object)?;
+
+    let snapshot = Snapshot::open(&state.pool, client.project_id, body.consistency_token.as_deref()).await?;
+    let tree = snapshot.expand(&object, &body.relation).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ExpandResponse {
+            tree,
+            consistency_token: snapshot.consistency_token(),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/relations/list-objects",
+    tag = "authz",
+    security(("client_auth" = [])),
+    request_body = ListObjectsRequestBody,
+    responses(
+        (status = 200, description = "Objects of the namespace on which the subject holds the relation", body = ListObjectsResponse),
+        (status = 400, description = "Malformed subject, unknown namespace or relation, or invalid consistency token"),
+        (status = 401, description = "Invalid client credentials"),
+    )
+)]
+pub async fn list_objects_handler(
+    client: ApplicationClient,
+    State(state): State<AppState>,
+    Json(body): Json<ListObjectsRequestBody>,
+) -> Result<impl IntoResponse, AppError> {
+    let subject = SubjectRef::parse("subject", &body.subject)?;
+
+    let snapshot = Snapshot::open(&state.pool, client.project_id, body.consistency_token.as_deref()).await?;
+    let objects = snapshot.list_objects(&body.namespace, &body.relation, &subject).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ListObjectsResponse {
+            objects,
+            consistency_token: snapshot.consistency_token(),
+        }),
+    ))
+}
@@ -84,7 +84,7 @@ pub mod tracing {
 pub mod net {
     use crate::config;
     use axum::http::Method;
-    use lazy_limit::{Duration, RuleConfig, init_rate_limiter};
+    use lazy_limit::{Duration, HttpMethod, RuleConfig, init_rate_limiter};
     use tower_http::cors;
     use tower_http::cors::{AllowHeaders, CorsLayer};
 
@@ -103,7 +103,8 @@ pub mod net {
     ///
     /// Configures per-route rate limits with a default of 10 requests per minute.
     ///
-    /// Auth endpoints allow 5 requests per 15 minutes.
+    /// Auth endpoints allow 5 requests per 15 minutes, and so do the HTML forms that sign a user
+    /// in with a password, counting only their submissions.
     /// Maximum memory usage is capped at 64 MB.
     pub async fn init_rate_limiting() {
         let env = config::env::env();
@@ -116,6 +117,8 @@ pub mod net {
                 ("/admin/login", RuleConfig::new(Duration::minutes(15), 5)),
                 ("/auth/register", RuleConfig::new(Duration::minutes(15), 5)),
                 ("/auth/login", RuleConfig::new(Duration::minutes(15), 5)),
+                ("/auth/device", RuleConfig::new(Duration::minutes(15), 5).for_methods(vec![HttpMethod::POST])),
+                ("/auth/consent", RuleConfig::new(Duration::minutes(15), 5).for_methods(vec![HttpMethod::POST])),
 
                 // identity
                 ("/admin/me", RuleConfig::new(Duration::minutes(1), 60)),
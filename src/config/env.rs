@@ -15,6 +15,8 @@ pub struct Env {
     pub user_access_token_duration_in_minutes: u8,
     pub admin_jwt_secret: String,
     pub user_jwt_secret: String,
+    pub jwt_issuer: String,
+    pub auth_events_retention_in_days: u16,
 }
 
 impl Env {
@@ -49,6 +51,11 @@ impl Env {
                 .expect("env: USER_JWT_SECRET must be set")
                 .parse()
                 .unwrap(),
+            jwt_issuer: dotenvy::var("JWT_ISSUER").expect("env: JWT_ISSUER must be set"),
+            auth_events_retention_in_days: dotenvy::var("AUTH_EVENTS_RETENTION_IN_DAYS")
+                .expect("env: AUTH_EVENTS_RETENTION_IN_DAYS must be set")
+                .parse()
+                .unwrap(),
         }
     }
 }
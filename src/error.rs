@@ -16,6 +16,13 @@ pub enum AppError {
     ValidationError(ValidationErrors),
     TimeError(std::time::SystemTimeError),
     TokenEncodeError(jsonwebtoken::errors::Error),
+    /// An OAuth error code the client acts on, such as `authorization_pending` (RFC 6749, section 5.2).
+    OAuth(&'static str),
+}
+
+#[derive(Serialize)]
+struct OAuthError {
+    error: &'static str,
 }
 
 #[derive(Serialize)]
@@ -64,6 +71,7 @@ impl IntoResponse for AppError {
                 error!("Token encode error: {}", err);
                 StatusCode::INTERNAL_SERVER_ERROR.into_response()
             }
+            AppError::OAuth(error) => (StatusCode::BAD_REQUEST, axum::Json(OAuthError { error })).into_response(),
         }
     }
 }
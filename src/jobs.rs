@@ -0,0 +1,207 @@
+use crate::config;
+use crate::error::AppError;
+use crate::id;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{error, info};
+
+const TICK_SLACK: Duration = Duration::from_secs(30);
+
+/// How long `job_runs` keeps a run, except each job's latest successful one.
+const JOB_RUNS_RETENTION_IN_DAYS: i32 = 30;
+
+/// Periodic maintenance work. Every replica schedules every job; a Postgres advisory lock and
+/// the last recorded run make sure each job runs on a single replica once per interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Job {
+    /// Marks pending admin invites past their expiry as `expired`.
+    ExpireInvites,
+    /// Deletes `auth_events` older than `AUTH_EVENTS_RETENTION_IN_DAYS`.
+    AuthEventsRetention,
+    /// Deletes expired device authorizations, freeing their user codes.
+    PurgeDeviceAuthorizations,
+    /// Deletes relation tuples whose deletion no snapshot can still be reading.
+    PurgeRelationTuples,
+    /// Deletes `job_runs` older than `JOB_RUNS_RETENTION_IN_DAYS`, keeping each job's latest successful run.
+    JobRunsRetention,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The job ran and changed this many rows.
+    Completed { affected_rows: u64 },
+    /// Another replica holds the lock or the job already ran within its interval.
+    Skipped,
+}
+
+impl Job {
+    pub const ALL: [Job; 5] = [
+        Job::ExpireInvites,
+        Job::AuthEventsRetention,
+        Job::PurgeDeviceAuthorizations,
+        Job::PurgeRelationTuples,
+        Job::JobRunsRetention,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Job::ExpireInvites => "expire_invites",
+            Job::AuthEventsRetention => "auth_events_retention",
+            Job::PurgeDeviceAuthorizations => "purge_device_authorizations",
+            Job::PurgeRelationTuples => "purge_relation_tuples",
+            Job::JobRunsRetention => "job_runs_retention",
+        }
+    }
+
+    pub fn interval(self) -> Duration {
+        match self {
+            Job::ExpireInvites => Duration::from_secs(5 * 60),
+            Job::AuthEventsRetention => Duration::from_secs(60 * 60),
+            Job::PurgeDeviceAuthorizations => Duration::from_secs(5 * 60),
+            Job::PurgeRelationTuples => Duration::from_secs(60 * 60),
+            Job::JobRunsRetention => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+
+    async fn execute(self, tx: &mut Transaction<'_, Postgres>) -> Result<u64, AppError> {
+        let result = match self {
+            Job::ExpireInvites => {
+                sqlx::query!(
+                    "UPDATE admin_invites SET status = 'expired' WHERE status = 'pending' AND expires_at < NOW()"
+                )
+                .execute(&mut **tx)
+                .await?
+            }
+            Job::AuthEventsRetention => {
+                let retention_days = config::env::env().auth_events_retention_in_days as i32;
+                sqlx::query!(
+                    "DELETE FROM auth_events WHERE occurred_at < NOW() - make_interval(days => $1)",
+                    retention_days,
+                )
+                .execute(&mut **tx)
+                .await?
+            }
+            Job::PurgeDeviceAuthorizations => {
+                sqlx::query!("DELETE FROM device_authorizations WHERE expires_at < NOW()")
+                    .execute(&mut **tx)
+                    .await?
+            }
+            Job::PurgeRelationTuples => {
+                // Snapshots read the latest revision for the length of one request; the margin
+                // leaves any request that opened one before the deletion well behind.
+                sqlx::query!("DELETE FROM relation_tuples WHERE deleted_at < NOW() - interval '10 minutes'")
+                    .execute(&mut **tx)
+                    .await?
+            }
+            Job::JobRunsRetention => {
+                // The latest successful run is what `run_once` measures the interval from.
+                sqlx::query!(
+                    r#"
+                    DELETE FROM job_runs r
+                    WHERE r.started_at < NOW() - make_interval(days => $1)
+                      AND r.id IS DISTINCT FROM (
+                          SELECT l.id FROM job_runs l
+                          WHERE l.job_name = r.job_name AND l.status = 'succeeded'
+                          ORDER BY l.started_at DESC
+                          LIMIT 1
+                      )
+                    "#,
+                    JOB_RUNS_RETENTION_IN_DAYS,
+                )
+                .execute(&mut **tx)
+                .await?
+            }
+        };
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Runs `job` unless another replica is running it or it already ran within its interval.
+/// The run is recorded in `job_runs`, failures included.
+pub async fn run_once(pool: &PgPool, job: Job) -> Result<RunOutcome, AppError> {
+    let mut tx = pool.begin().await?;
+
+    // Transaction-scoped, so the lock is released even if this replica dies mid-run.
+    let locked = sqlx::query_scalar!("SELECT pg_try_advisory_xact_lock(hashtext('job:' || $1))", job.name())
+        .fetch_one(&mut *tx)
+        .await?
+        .unwrap_or(false);
+
+    if !locked {
+        return Ok(RunOutcome::Skipped);
+    }
+
+    let last_run = sqlx::query_scalar!(
+        "SELECT MAX(started_at) FROM job_runs WHERE job_name = $1 AND status = 'succeeded'",
+        job.name(),
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // Ticks drift by a few milliseconds, so a run one interval later must not count as early.
+    let started_at = OffsetDateTime::now_utc();
+    if last_run.is_some_and(|last_run| started_at - last_run < job.interval() - TICK_SLACK) {
+        return Ok(RunOutcome::Skipped);
+    }
+
+    match job.execute(&mut tx).await {
+        Ok(affected_rows) => {
+            sqlx::query!(
+                r#"
+                    INSERT INTO job_runs (id, job_name, status, started_at, affected_rows)
+                    VALUES ($1, $2, 'succeeded', $3, $4)
+                "#,
+                id::new_uuid(),
+                job.name(),
+                started_at,
+                affected_rows as i64,
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            info!(job = job.name(), affected_rows, "job run succeeded");
+            Ok(RunOutcome::Completed { affected_rows })
+        }
+        Err(err) => {
+            // The job's changes are rolled back; the failure is recorded outside the transaction.
+            tx.rollback().await?;
+            let message = match &err {
+                AppError::Sqlx(sqlx_err) => sqlx_err.to_string(),
+                _ => "job failed".to_string(),
+            };
+
+            sqlx::query!(
+                "INSERT INTO job_runs (id, job_name, status, started_at, error) VALUES ($1, $2, 'failed', $3, $4)",
+                id::new_uuid(),
+                job.name(),
+                started_at,
+                message,
+            )
+            .execute(pool)
+            .await?;
+
+            error!(job = job.name(), error = %message, "job run failed");
+            Err(err)
+        }
+    }
+}
+
+/// Schedules every job on the current Tokio runtime. Each job is attempted once per
+/// interval; `run_once` decides whether this replica actually runs it.
+pub fn spawn(pool: PgPool) {
+    for job in Job::ALL {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(job.interval());
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                // Failures are recorded by `run_once`; the next tick simply tries again.
+                let _ = run_once(&pool, job).await;
+            }
+        });
+    }
+}
@@ -11,7 +11,71 @@ use std::time::{Duration, SystemTime};
 pub struct Claims {
     pub sub: String,
     pub user_type: String,
+    pub iss: String,
+    /// `client_id` of the application the token was issued to. User tokens only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    pub iat: u64,
+    pub nbf: u64,
     pub exp: u64,
+    /// Project the account belongs to. User tokens only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// Space-delimited permission names granted on the audience application. User tokens only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// The admin acting as the user (RFC 8693, section 4.1). Impersonation tokens only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+}
+
+/// The `act` claim: who is acting on behalf of `sub`. Delegation chains nest, the current actor
+/// outermost (RFC 8693, section 4.1).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Actor {
+    /// `admin_users.id` for admins, `user_accounts.id` for user tokens acting through token exchange.
+    pub sub: String,
+    /// The actor before this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<Actor>>,
+}
+
+/// What a user token is issued for: the account, its project, the requesting application and
+/// the permission names granted to the account on that application.
+pub struct UserTokenSubject<'a> {
+    pub account_id: &'a str,
+    pub project_id: &'a str,
+    pub client_id: &'a str,
+    pub scopes: &'a [String],
+}
+
+/// Claims of a signed admin invite link. Signed with the admin key, but `user_type` is
+/// `"admin_invite"`, so the admin middleware never accepts one as an access token.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteClaims {
+    /// `admin_invites.id`.
+    pub sub: String,
+    pub user_type: String,
+    pub iss: String,
+    pub iat: u64,
+    pub nbf: u64,
+    pub exp: u64,
+    pub email: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    pub role: String,
+}
+
+/// What an invite link is bound to: the invite, the address it was sent to, its scope and role.
+pub struct InviteTokenSubject<'a> {
+    pub invite_id: &'a str,
+    pub email: &'a str,
+    pub org_id: Option<&'a str>,
+    pub project_id: Option<&'a str>,
+    pub role: &'a str,
+    pub expires_at: time::OffsetDateTime,
 }
 
 fn get_second_word(origin: &str) -> Option<&str> {
@@ -31,56 +95,246 @@ enum UserKind {
     Admin,
 }
 
-fn decode_token(token: &str, user_kind: UserKind) -> Result<TokenData<Claims>, AppError> {
+fn decode_token(token: &str, user_kind: UserKind, audiences: &[&str]) -> Result<TokenData<Claims>, AppError> {
     let env = config::env::env();
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[&env.jwt_issuer]);
+    validation.validate_nbf = true;
+
     let secret = match user_kind {
-        UserKind::Admin => &env.admin_jwt_secret,
-        UserKind::User => &env.user_jwt_secret,
+        UserKind::Admin => {
+            validation.set_required_spec_claims(&["exp", "iss", "nbf"]);
+            &env.admin_jwt_secret
+        }
+        UserKind::User => {
+            validation.set_required_spec_claims(&["exp", "iss", "nbf", "aud"]);
+            validation.set_audience(audiences);
+            &env.user_jwt_secret
+        }
     };
 
-    decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &Validation::new(Algorithm::HS256),
-    )
-    .map_err(|_| AppError::InvalidToken)
+    decode::<Claims>(token, &DecodingKey::from_secret(secret.as_ref()), &validation).map_err(|_| AppError::InvalidToken)
 }
 
 pub fn decode_admin_token(token: &str) -> Result<TokenData<Claims>, AppError> {
-    decode_token(token, UserKind::Admin)
+    decode_token(token, UserKind::Admin, &[])
+}
+
+/// Decodes a user token issued by this server.
+///
+/// `audiences` are the `client_id`s the token may have been issued to; a token for any other
+/// application, or without an `aud` claim, is rejected.
+pub fn decode_user_token(token: &str, audiences: &[&str]) -> Result<TokenData<Claims>, AppError> {
+    if audiences.is_empty() {
+        return Err(AppError::InvalidToken);
+    }
+    decode_token(token, UserKind::User, audiences)
 }
 
-pub fn decode_user_token(token: &str) -> Result<TokenData<Claims>, AppError> {
-    decode_token(token, UserKind::User)
+/// The `project_id` claim of a user token, read without checking the signature. Only for finding
+/// the applications to pass to `decode_user_token`; never trust it on its own.
+pub fn unverified_project_id(token: &str) -> Result<String, AppError> {
+    jsonwebtoken::dangerous::insecure_decode::<Claims>(token)
+        .ok()
+        .and_then(|data| data.claims.project_id)
+        .ok_or(AppError::InvalidToken)
 }
 
-fn generate_token(user_id: &str, user_kind: UserKind) -> Result<String, AppError> {
+pub fn generate_admin_token(user_id: &str) -> Result<String, AppError> {
+    encode_admin_token(user_id, "admin")
+}
+
+/// Admin access token for a service account. Its `user_type` is `service`, so the admin API can
+/// keep it off the endpoints reserved for human sessions.
+pub fn generate_service_account_token(service_account_id: &str) -> Result<String, AppError> {
+    encode_admin_token(service_account_id, "service")
+}
+
+fn encode_admin_token(user_id: &str, user_type: &str) -> Result<String, AppError> {
     let env = config::env::env();
 
     let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
-    let (duration, user_type, secret) = match user_kind {
-        UserKind::Admin => (
-            env.admin_access_token_duration_in_minutes,
-            "admin",
-            &env.admin_jwt_secret,
-        ),
-        UserKind::User => (env.user_access_token_duration_in_minutes, "user", &env.user_jwt_secret),
-    };
-    let exp = now.add(Duration::from_mins(duration as u64)).as_secs();
+    let exp = now
+        .add(Duration::from_mins(env.admin_access_token_duration_in_minutes as u64))
+        .as_secs();
 
     let claims = Claims {
         sub: user_id.to_string(),
         user_type: user_type.to_string(),
+        iss: env.jwt_issuer.clone(),
+        aud: None,
+        iat: now.as_secs(),
+        nbf: now.as_secs(),
         exp,
+        project_id: None,
+        scope: None,
+        act: None,
     };
 
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_ref())).map_err(AppError::TokenEncodeError)
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(env.admin_jwt_secret.as_ref()),
+    )
+    .map_err(AppError::TokenEncodeError)
 }
 
-pub fn generate_admin_token(user_id: &str) -> Result<String, AppError> {
-    generate_token(user_id, UserKind::Admin)
+pub fn generate_user_token(subject: &UserTokenSubject) -> Result<String, AppError> {
+    let env = config::env::env();
+    encode_user_token(
+        subject,
+        Duration::from_mins(env.user_access_token_duration_in_minutes as u64),
+        None,
+    )
+}
+
+/// Issues a user token that an admin acts with, naming them in `act`. It lasts `duration`
+/// instead of the usual user token lifetime.
+pub fn generate_impersonation_token(
+    subject: &UserTokenSubject,
+    admin_id: &str,
+    duration: Duration,
+) -> Result<String, AppError> {
+    encode_user_token(
+        subject,
+        duration,
+        Some(Actor {
+            sub: admin_id.to_string(),
+            act: None,
+        }),
+    )
 }
 
-pub fn generate_user_token(user_id: &str) -> Result<String, AppError> {
-    generate_token(user_id, UserKind::User)
+/// Issues the token a token exchange produces: `act` carries the delegation chain, and
+/// `duration` is capped by the caller so it does not outlive the subject token.
+pub fn generate_exchanged_token(
+    subject: &UserTokenSubject,
+    act: Option<Actor>,
+    duration: Duration,
+) -> Result<String, AppError> {
+    encode_user_token(subject, duration, act)
+}
+
+fn encode_user_token(subject: &UserTokenSubject, duration: Duration, act: Option<Actor>) -> Result<String, AppError> {
+    let env = config::env::env();
+
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+    let exp = now.add(duration).as_secs();
+
+    let claims = Claims {
+        sub: subject.account_id.to_string(),
+        user_type: "user".to_string(),
+        iss: env.jwt_issuer.clone(),
+        aud: Some(subject.client_id.to_string()),
+        iat: now.as_secs(),
+        nbf: now.as_secs(),
+        exp,
+        project_id: Some(subject.project_id.to_string()),
+        scope: Some(subject.scopes.join(" ")),
+        act,
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(env.user_jwt_secret.as_ref()),
+    )
+    .map_err(AppError::TokenEncodeError)
+}
+
+/// Signs the invite link for an email invite. The token expires together with the invite.
+pub fn generate_invite_token(subject: &InviteTokenSubject) -> Result<String, AppError> {
+    let env = config::env::env();
+    let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+
+    let claims = InviteClaims {
+        sub: subject.invite_id.to_string(),
+        user_type: "admin_invite".to_string(),
+        iss: env.jwt_issuer.clone(),
+        iat: now.as_secs(),
+        nbf: now.as_secs(),
+        exp: subject.expires_at.unix_timestamp() as u64,
+        email: subject.email.to_string(),
+        org_id: subject.org_id.map(str::to_string),
+        project_id: subject.project_id.map(str::to_string),
+        role: subject.role.to_string(),
+    };
+
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(env.admin_jwt_secret.as_ref()),
+    )
+    .map_err(AppError::TokenEncodeError)
+}
+
+pub fn decode_invite_token(token: &str) -> Result<InviteClaims, AppError> {
+    let env = config::env::env();
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_issuer(&[&env.jwt_issuer]);
+    validation.set_required_spec_claims(&["exp", "iss", "nbf"]);
+    validation.validate_nbf = true;
+
+    let claims = decode::<InviteClaims>(
+        token,
+        &DecodingKey::from_secret(env.admin_jwt_secret.as_ref()),
+        &validation,
+    )
+    .map_err(|_| AppError::InvalidToken)?
+    .claims;
+
+    if claims.user_type != "admin_invite" {
+        return Err(AppError::InvalidToken);
+    }
+    Ok(claims)
+}
+
+/// Claims of a client assertion (RFC 7523) that a service account signs with its own key.
+/// `aud` is checked during validation and not kept.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClientAssertionClaims {
+    /// The service account ID, like `iss`.
+    pub sub: String,
+    pub iss: String,
+    pub exp: u64,
+    pub jti: String,
+}
+
+/// Parses a public key registered for client assertions. `algorithm` is `RS256`, `ES256` or
+/// `EdDSA`, and `pem` the matching SubjectPublicKeyInfo PEM.
+pub fn assertion_key(algorithm: &str, pem: &str) -> Option<(Algorithm, DecodingKey)> {
+    let (algorithm, key) = match algorithm {
+        "RS256" => (Algorithm::RS256, DecodingKey::from_rsa_pem(pem.as_bytes())),
+        "ES256" => (Algorithm::ES256, DecodingKey::from_ec_pem(pem.as_bytes())),
+        "EdDSA" => (Algorithm::EdDSA, DecodingKey::from_ed_pem(pem.as_bytes())),
+        _ => return None,
+    };
+    Some((algorithm, key.ok()?))
+}
+
+/// Reads `sub` from a client assertion before its signature is checked, to find the keys to
+/// check it with. Nothing else may be trusted until `decode_client_assertion` succeeds.
+pub fn client_assertion_subject(token: &str) -> Result<String, AppError> {
+    jsonwebtoken::dangerous::insecure_decode::<ClientAssertionClaims>(token)
+        .map(|data| data.claims.sub)
+        .map_err(|_| AppError::InvalidToken)
+}
+
+/// Verifies a client assertion with one of the service account's keys: `iss` and `sub` must be
+/// `client_id`, and `aud` must be `audience`, the token endpoint's URL.
+pub fn decode_client_assertion(
+    token: &str,
+    (algorithm, key): &(Algorithm, DecodingKey),
+    client_id: &str,
+    audience: &str,
+) -> Result<ClientAssertionClaims, AppError> {
+    let mut validation = Validation::new(*algorithm);
+    validation.set_issuer(&[client_id]);
+    validation.set_audience(&[audience]);
+    validation.sub = Some(client_id.to_string());
+    validation.set_required_spec_claims(&["exp", "iss", "sub", "aud"]);
+
+    decode::<ClientAssertionClaims>(token, key, &validation)
+        .map(|data| data.claims)
+        .map_err(|_| AppError::InvalidToken)
 }
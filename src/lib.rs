@@ -1,9 +1,12 @@
 pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod authz;
 pub mod config;
 pub mod crypto;
 pub mod error;
 pub mod id;
+pub mod jobs;
 pub mod jwt;
 pub mod openapi;
 pub mod pagination;
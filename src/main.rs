@@ -1,19 +1,10 @@
-use router::AppState;
 use std::net::Ipv4Addr;
+use study_auth::config;
+use study_auth::jobs;
+use study_auth::router::{self, AppState};
 use tokio::net::TcpListener;
 use tracing::{error, info};
 
-mod admin;
-mod auth;
-mod config;
-mod crypto;
-mod error;
-mod id;
-mod jwt;
-mod openapi;
-mod pagination;
-mod router;
-
 #[tokio::main]
 async fn main() {
     config::tracing::init_tracing();
@@ -27,6 +18,8 @@ async fn main() {
         }
     };
 
+    jobs::spawn(pool.clone());
+
     let state = AppState::new(pool);
     let trace_layer = config::tracing::get_trace_layer();
     let cors_layer = config::net::get_cors_layer();
@@ -34,8 +27,7 @@ async fn main() {
         .layer(real::RealIpLayer::default())
         .layer(axum_governor::GovernorLayer::default());
 
-    let app_router = router::routes()
-        .with_state(state)
+    let app_router = router::routes(state)
         .layer(trace_layer)
         .layer(cors_layer)
         .layer(rate_limiter_layer)
@@ -15,6 +15,10 @@ impl Modify for BearerAuth {
                         .build(),
                 ),
             );
+            components.add_security_scheme(
+                "client_auth",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Basic).build()),
+            );
         }
     }
 }
@@ -26,6 +30,7 @@ impl Modify for BearerAuth {
     tags(
         (name = "auth", description = "User authentication"),
         (name = "admin", description = "Admin management"),
+        (name = "authz", description = "Authorization decisions for relying services"),
     )
 )]
 pub struct ApiDoc;
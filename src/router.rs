@@ -1,5 +1,6 @@
 use crate::admin;
 use crate::auth;
+use crate::authz;
 use crate::openapi::ApiDoc;
 use axum::Router;
 use sqlx::{Pool, Postgres};
@@ -18,11 +19,14 @@ impl AppState {
     }
 }
 
-pub fn routes() -> Router<AppState> {
+pub fn routes(state: AppState) -> Router {
     let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/admin", admin::router::get_router())
+        .nest("/admin", admin::router::get_router(state.clone()))
         .nest("/auth", auth::router::get_router())
+        .nest("/authz", authz::router::get_router())
         .split_for_parts();
 
-    router.merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", api))
+    router
+        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", api))
+        .with_state(state)
 }
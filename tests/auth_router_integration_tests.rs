@@ -0,0 +1,732 @@
+#![allow(dead_code)]
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::*;
+use serde_json::json;
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+fn user_token(account_id: uuid::Uuid, project_id: uuid::Uuid, client_id: uuid::Uuid) -> String {
+    init_test_env();
+    study_auth::jwt::generate_user_token(&study_auth::jwt::UserTokenSubject {
+        account_id: &account_id.to_string(),
+        project_id: &project_id.to_string(),
+        client_id: &client_id.to_string(),
+        scopes: &[],
+    })
+    .unwrap_or_else(|_| panic!("failed to generate user token for tests"))
+}
+
+// ─── DB helpers ───────────────────────────────────────────────────────────────
+
+async fn grant_permission(pool: &PgPool, account_id: uuid::Uuid, application_id: uuid::Uuid, name: &str) {
+    let permission_id = insert_permission(pool, application_id, name).await;
+    sqlx::query("INSERT INTO account_scopes (account_id, permission_id) VALUES ($1, $2)")
+        .bind(account_id)
+        .bind(permission_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+// ─── POST /auth/login ─────────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn login_returns_user_token_for_valid_credentials(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+
+    let response = test_app(pool)
+        .oneshot(json_request(
+            "POST",
+            "/auth/login",
+            json!({
+                "identifier": "user@example.com",
+                "method_type": "email",
+                "password": "password-123",
+                "client_id": client_id.to_string(),
+            }),
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    let claims = study_auth::jwt::decode_user_token(body["access_token"].as_str().unwrap(), &[&client_id.to_string()])
+        .unwrap_or_else(|_| panic!("failed to decode user token"))
+        .claims;
+    assert_eq!(claims.sub, account_id.to_string());
+    assert_eq!(claims.user_type, "user");
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn login_token_carries_project_audience_and_application_scopes(
+    pool: PgPool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let (other_application_id, other_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+    grant_permission(&pool, account_id, application_id, "orders:write").await;
+    grant_permission(&pool, account_id, application_id, "orders:read").await;
+    grant_permission(&pool, account_id, other_application_id, "billing:read").await;
+
+    let response = test_app(pool)
+        .oneshot(json_request(
+            "POST",
+            "/auth/login",
+            json!({
+                "identifier": "user@example.com",
+                "method_type": "email",
+                "password": "password-123",
+                "client_id": client_id.to_string(),
+            }),
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    let access_token = body["access_token"].as_str().unwrap();
+    let claims = study_auth::jwt::decode_user_token(access_token, &[&client_id.to_string()])
+        .unwrap_or_else(|_| panic!("failed to decode user token"))
+        .claims;
+
+    assert_eq!(claims.iss, "http://localhost:3000");
+    assert_eq!(claims.aud, Some(client_id.to_string()));
+    assert_eq!(claims.project_id, Some(project_id.to_string()));
+    assert_eq!(claims.scope.as_deref(), Some("orders:read orders:write"));
+    assert!(claims.nbf <= claims.exp && claims.iat == claims.nbf);
+
+    // The audience is enforced when the caller expects a specific client.
+    assert!(study_auth::jwt::decode_user_token(access_token, &[&other_client_id.to_string()]).is_err());
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn login_rejects_wrong_password(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    insert_user_account(&pool, project_id, "user@example.com").await;
+
+    let response = test_app(pool.clone())
+        .oneshot(json_request(
+            "POST",
+            "/auth/login",
+            json!({
+                "identifier": "user@example.com",
+                "method_type": "email",
+                "password": "wrong-password",
+                "client_id": client_id.to_string(),
+            }),
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let failed_events: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM auth_events WHERE event_type = 'user_login' AND success = false")
+            .fetch_one(&pool)
+            .await?;
+    assert_eq!(failed_events, 1);
+    Ok(())
+}
+
+// ─── GET /auth/me ─────────────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn me_returns_account_and_granted_scopes(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+    grant_permission(&pool, account_id, application_id, "orders:read").await;
+    grant_permission(&pool, account_id, application_id, "orders:write").await;
+    // Scopes on another application of the project are not part of this token's audience.
+    let other_application_id = insert_application(&pool, project_id).await;
+    grant_permission(&pool, account_id, other_application_id, "billing:read").await;
+
+    let token = user_token(account_id, project_id, client_id);
+
+    let response = test_app(pool).oneshot(auth_request("GET", "/auth/me", &token)).await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    assert_eq!(body["account_id"], account_id.to_string());
+    assert_eq!(body["project_id"], project_id.to_string());
+    assert_eq!(body["identifier"], "user@example.com");
+    assert_eq!(body["scopes"], json!(["orders:read", "orders:write"]));
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn me_rejects_admin_token(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+
+    // An admin token whose subject happens to be an account id must not be accepted.
+    let token = study_auth::jwt::generate_admin_token(&account_id.to_string())
+        .unwrap_or_else(|_| panic!("failed to generate admin token"));
+
+    let response = test_app(pool).oneshot(auth_request("GET", "/auth/me", &token)).await?;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn me_rejects_token_for_application_of_another_project(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let other_project_id = insert_org_with_project(&pool, "Other").await;
+    let (_application_id, other_client_id) = insert_application_with_client_id(&pool, other_project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+
+    let token = user_token(account_id, project_id, other_client_id);
+
+    let response = test_app(pool).oneshot(auth_request("GET", "/auth/me", &token)).await?;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn me_rejects_token_for_unknown_audience(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    insert_application(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "user@example.com").await;
+
+    let token = user_token(account_id, project_id, study_auth::id::new_uuid());
+
+    let response = test_app(pool).oneshot(auth_request("GET", "/auth/me", &token)).await?;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+// ─── POST /auth/register ──────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn register_assigns_default_roles_that_expand_into_token_scopes(
+    pool: PgPool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+
+    let role_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO project_roles (id, project_id, name, is_default) VALUES ($1, $2, 'member', true)")
+        .bind(role_id)
+        .bind(project_id)
+        .execute(&pool)
+        .await?;
+    let permission_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO permissions (id, app_id, name, description) VALUES ($1, $2, 'orders:read', '')")
+        .bind(permission_id)
+        .bind(application_id)
+        .execute(&pool)
+        .await?;
+    sqlx::query("INSERT INTO project_role_permissions (role_id, permission_id) VALUES ($1, $2)")
+        .bind(role_id)
+        .bind(permission_id)
+        .execute(&pool)
+        .await?;
+
+    let register_response = test_app(pool.clone())
+        .oneshot(json_request(
+            "POST",
+            "/auth/register",
+            json!({
+                "identifier": "new@example.com",
+                "method_type": "email",
+                "password": "password-123",
+                "client_id": client_id.to_string(),
+                "profile": {},
+            }),
+        ))
+        .await?;
+    assert_eq!(register_response.status(), StatusCode::CREATED);
+
+    let login_response = test_app(pool)
+        .oneshot(json_request(
+            "POST",
+            "/auth/login",
+            json!({
+                "identifier": "new@example.com",
+                "method_type": "email",
+                "password": "password-123",
+                "client_id": client_id.to_string(),
+            }),
+        ))
+        .await?;
+    assert_eq!(login_response.status(), StatusCode::OK);
+
+    let body = json_body(login_response).await;
+    let claims = study_auth::jwt::decode_user_token(body["access_token"].as_str().unwrap(), &[&client_id.to_string()])
+        .unwrap_or_else(|_| panic!("failed to decode user token"))
+        .claims;
+    assert_eq!(claims.scope.as_deref(), Some("orders:read"));
+    Ok(())
+}
+
+// ─── POST /auth/token ─────────────────────────────────────────────────────────
+
+const TOKEN_EXCHANGE_FORM: &str = "grant_type=urn:ietf:params:oauth:grant-type:token-exchange&subject_token_type=urn:ietf:params:oauth:token-type:access_token";
+
+fn client_form_request(client_id: uuid::Uuid, form: &str) -> Request<Body> {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    let credentials = STANDARD.encode(format!("{client_id}:{CLIENT_SECRET}"));
+    Request::builder()
+        .method("POST")
+        .uri("/auth/token")
+        .header("content-type", "application/x-www-form-urlencoded")
+        .header("authorization", format!("Basic {credentials}"))
+        .body(Body::from(form.to_string()))
+        .unwrap()
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn token_exchange_narrows_scopes_and_nests_actors(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (frontend_id, frontend_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let (orders_id, orders_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "alice@example.com").await;
+    let actor_id = insert_user_account(&pool, project_id, "gateway@example.com").await;
+    grant_permission(&pool, account_id, frontend_id, "profile:read").await;
+    grant_permission(&pool, account_id, frontend_id, "profile:write").await;
+    grant_permission(&pool, account_id, frontend_id, "profile:delete").await;
+    grant_permission(&pool, account_id, orders_id, "orders:read").await;
+
+    // A support admin's impersonation token, so the exchange has an `act` chain to extend.
+    let admin_id = study_auth::id::new_uuid();
+    let subject_token = study_auth::jwt::generate_impersonation_token(
+        &study_auth::jwt::UserTokenSubject {
+            account_id: &account_id.to_string(),
+            project_id: &project_id.to_string(),
+            client_id: &frontend_client_id.to_string(),
+            scopes: &["profile:read".to_string(), "profile:write".to_string()],
+        },
+        &admin_id.to_string(),
+        std::time::Duration::from_secs(600),
+    )
+    .unwrap_or_else(|_| panic!("failed to generate subject token"));
+    let actor_token = user_token(actor_id, project_id, frontend_client_id);
+
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!(
+                "{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&audience={frontend_client_id}&scope=profile:read\
+                 &actor_token={actor_token}&actor_token_type=urn:ietf:params:oauth:token-type:access_token"
+            ),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(
+        body["issued_token_type"],
+        "urn:ietf:params:oauth:token-type:access_token"
+    );
+    assert_eq!(body["scope"], "profile:read");
+    assert!(body["expires_in"].as_u64().unwrap() <= 600);
+
+    let claims = study_auth::jwt::decode_user_token(
+        body["access_token"].as_str().unwrap(),
+        &[&frontend_client_id.to_string()],
+    )
+    .unwrap_or_else(|_| panic!("exchanged token should decode"))
+    .claims;
+    assert_eq!(claims.sub, account_id.to_string());
+    assert_eq!(claims.scope.as_deref(), Some("profile:read"));
+    let act = claims.act.unwrap();
+    assert_eq!(act.sub, actor_id.to_string());
+    assert_eq!(act.act.map(|act| act.sub), Some(admin_id.to_string()));
+
+    // The subject token does not carry profile:delete, although the account holds it.
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&scope=profile:delete"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(json_body(response).await["errors"]["scope"].is_array());
+
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["scope"], "profile:read profile:write");
+
+    // Delegating to the orders application carries what the account holds there.
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&audience={orders_client_id}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["scope"], "orders:read");
+    let claims =
+        study_auth::jwt::decode_user_token(body["access_token"].as_str().unwrap(), &[&orders_client_id.to_string()])
+            .unwrap_or_else(|_| panic!("exchanged token should decode"))
+            .claims;
+    assert_eq!(claims.sub, account_id.to_string());
+    assert_eq!(claims.act.map(|act| act.sub), Some(admin_id.to_string()));
+
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!(
+                "{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&audience={orders_client_id}&scope=profile:read"
+            ),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(json_body(response).await["errors"]["scope"].is_array());
+
+    // A third-party audience needs the user's approval first.
+    let (reviews_id, reviews_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    sqlx::query("UPDATE applications SET first_party = false WHERE id = $1")
+        .bind(reviews_id)
+        .execute(&pool)
+        .await?;
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&audience={reviews_client_id}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(json_body(response).await["errors"]["audience"].is_array());
+
+    // Only the subject token's audience may exchange it.
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            orders_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}&audience={orders_client_id}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let other_project_id = insert_org_with_project(&pool, "Other").await;
+    let (_, other_client_id) = insert_application_with_client_id(&pool, other_project_id).await;
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            other_client_id,
+            &format!("{TOKEN_EXCHANGE_FORM}&subject_token={subject_token}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = test_app(pool.clone())
+        .oneshot(client_form_request(
+            frontend_client_id,
+            &format!("grant_type=password&subject_token={subject_token}"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+// ─── Device authorization grant ───────────────────────────────────────────────
+
+fn device_poll(client_id: uuid::Uuid, device_code: &str) -> Request<Body> {
+    form_request(
+        "/auth/token",
+        &format!(
+            "grant_type=urn:ietf:params:oauth:grant-type:device_code&client_id={client_id}&device_code={device_code}"
+        ),
+    )
+}
+
+/// Lets the next poll through without waiting out the interval.
+async fn reset_poll_clock(pool: &PgPool) {
+    sqlx::query("UPDATE device_authorizations SET last_polled_at = NULL")
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn device_grant_polls_until_user_approves(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "bob@example.com").await;
+    grant_permission(&pool, account_id, application_id, "tv:watch").await;
+    grant_permission(&pool, account_id, application_id, "tv:purchase").await;
+
+    let response = test_app(pool.clone())
+        .oneshot(form_request(
+            "/auth/device/code",
+            &format!("client_id={client_id}&scope=tv:watch"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    let device_code = body["device_code"].as_str().unwrap().to_string();
+    let user_code = body["user_code"].as_str().unwrap().to_string();
+    assert_eq!(user_code.len(), 9);
+    assert!(body["verification_uri"].as_str().unwrap().ends_with("/auth/device"));
+    assert_eq!(body["interval"], 5);
+
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, &device_code))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "authorization_pending");
+
+    // Polling again right away is too fast.
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, &device_code))
+        .await?;
+    assert_eq!(json_body(response).await["error"], "slow_down");
+    let interval: i32 = sqlx::query_scalar("SELECT poll_interval_secs FROM device_authorizations")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(interval, 10);
+
+    // The page accepts the code typed in lowercase and without the dash.
+    let typed = user_code.replace('-', "").to_lowercase();
+    let response = test_app(pool.clone())
+        .oneshot(
+            Request::builder()
+                .uri(format!("/auth/device?user_code={typed}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let page = text_body(response).await;
+    assert!(page.contains("tv:watch"));
+    assert!(page.contains(&user_code));
+
+    let approve = format!(
+        "user_code={user_code}&method_type=email&identifier=bob%40example.com&password=wrong-password&action=approve"
+    );
+    let response = test_app(pool.clone())
+        .oneshot(form_request("/auth/device", &approve))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let approve = approve.replace("wrong-password", "password-123");
+    let response = test_app(pool.clone())
+        .oneshot(form_request("/auth/device", &approve))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(text_body(response).await.contains("Device connected"));
+
+    reset_poll_clock(&pool).await;
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, &device_code))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    assert_eq!(body["scope"], "tv:watch");
+    assert!(body.get("issued_token_type").is_none());
+    let claims = study_auth::jwt::decode_user_token(body["access_token"].as_str().unwrap(), &[&client_id.to_string()])
+        .unwrap_or_else(|_| panic!("device token should decode"))
+        .claims;
+    assert_eq!(claims.sub, account_id.to_string());
+
+    // An approval is redeemed once.
+    reset_poll_clock(&pool).await;
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, &device_code))
+        .await?;
+    assert_eq!(json_body(response).await["error"], "invalid_grant");
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn device_grant_reports_denied_and_expired_codes(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    insert_user_account(&pool, project_id, "bob@example.com").await;
+
+    let response = test_app(pool.clone())
+        .oneshot(form_request(
+            "/auth/device/code",
+            &format!("client_id={client_id}&scope=unknown"),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let mut codes = Vec::new();
+    for _ in 0..2 {
+        let response = test_app(pool.clone())
+            .oneshot(form_request("/auth/device/code", &format!("client_id={client_id}")))
+            .await?;
+        let body = json_body(response).await;
+        codes.push((
+            body["device_code"].as_str().unwrap().to_string(),
+            body["user_code"].as_str().unwrap().to_string(),
+        ));
+    }
+    let (denied_code, denied_user_code) = &codes[0];
+    let (expired_code, expired_user_code) = &codes[1];
+
+    let deny = format!(
+        "user_code={denied_user_code}&method_type=email&identifier=bob%40example.com&password=password-123&action=deny"
+    );
+    let response = test_app(pool.clone())
+        .oneshot(form_request("/auth/device", &deny))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, denied_code))
+        .await?;
+    assert_eq!(json_body(response).await["error"], "access_denied");
+
+    sqlx::query("UPDATE device_authorizations SET expires_at = NOW() - INTERVAL '1 second' WHERE user_code = $1")
+        .bind(expired_user_code)
+        .execute(&pool)
+        .await?;
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(client_id, expired_code))
+        .await?;
+    assert_eq!(json_body(response).await["error"], "expired_token");
+    let response = test_app(pool.clone())
+        .oneshot(
+            Request::builder()
+                .uri(format!("/auth/device?user_code={expired_user_code}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert!(text_body(response).await.contains("invalid or has expired"));
+
+    // A device code only works with the client it was issued to.
+    let (_, other_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let response = test_app(pool.clone())
+        .oneshot(device_poll(other_client_id, denied_code))
+        .await?;
+    assert_eq!(json_body(response).await["error"], "invalid_grant");
+    Ok(())
+}
+
+// ─── Consent ──────────────────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn third_party_application_needs_consent_and_loses_access_when_revoked(
+    pool: PgPool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    sqlx::query("UPDATE applications SET first_party = false WHERE id = $1")
+        .bind(application_id)
+        .execute(&pool)
+        .await?;
+    let account_id = insert_user_account(&pool, project_id, "carol@example.com").await;
+    grant_permission(&pool, account_id, application_id, "profile:read").await;
+    grant_permission(&pool, account_id, application_id, "profile:write").await;
+
+    let login = |scope: Option<&str>| {
+        json_request(
+            "POST",
+            "/auth/login",
+            json!({
+                "client_id": client_id.to_string(),
+                "method_type": "email",
+                "identifier": "carol@example.com",
+                "password": "password-123",
+                "scope": scope,
+            }),
+        )
+    };
+
+    let response = test_app(pool.clone()).oneshot(login(Some("profile:read"))).await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(response).await["error"], "consent_required");
+
+    let response = test_app(pool.clone())
+        .oneshot(
+            Request::builder()
+                .uri(format!("/auth/consent?client_id={client_id}&scope=profile:read"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(text_body(response).await.contains("<li>profile:read</li>"));
+
+    let response = test_app(pool.clone())
+        .oneshot(form_request(
+            "/auth/consent",
+            &format!(
+                "client_id={client_id}&scope=profile:read&method_type=email&identifier=carol%40example.com\
+                 &password=password-123&action=allow"
+            ),
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The approved set is remembered; a wider request asks again.
+    let response = test_app(pool.clone()).oneshot(login(Some("profile:read"))).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let token = json_body(response).await["access_token"].as_str().unwrap().to_string();
+    let response = test_app(pool.clone()).oneshot(login(None)).await?;
+    assert_eq!(json_body(response).await["error"], "consent_required");
+
+    let response = test_app(pool.clone())
+        .oneshot(auth_request("GET", "/auth/me", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(response).await["scopes"], json!(["profile:read"]));
+
+    // The third-party application cannot manage the user's consents with the token it was given.
+    let response = test_app(pool.clone())
+        .oneshot(auth_request("GET", "/auth/consents", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let response = test_app(pool.clone())
+        .oneshot(auth_request("DELETE", &format!("/auth/consents/{client_id}"), &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // The project's own application can.
+    let (_, first_party_client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let first_party_token = user_token(account_id, project_id, first_party_client_id);
+    let response = test_app(pool.clone())
+        .oneshot(auth_request("GET", "/auth/consents", &first_party_token))
+        .await?;
+    let body = json_body(response).await;
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["client_id"], client_id.to_string());
+    assert_eq!(body[0]["scopes"], json!(["profile:read"]));
+
+    let response = test_app(pool.clone())
+        .oneshot(auth_request(
+            "DELETE",
+            &format!("/auth/consents/{client_id}"),
+            &first_party_token,
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Tokens issued under the revoked consent stop working at once.
+    let response = test_app(pool.clone())
+        .oneshot(auth_request("GET", "/auth/me", &token))
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = test_app(pool.clone()).oneshot(login(Some("profile:read"))).await?;
+    assert_eq!(json_body(response).await["error"], "consent_required");
+    Ok(())
+}
@@ -0,0 +1,351 @@
+#![allow(dead_code)]
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use common::*;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+/// JSON request authenticated with HTTP Basic client credentials.
+fn client_json_request(uri: &str, body: Value, client_id: uuid::Uuid, client_secret: &str) -> Request<Body> {
+    let credentials = STANDARD.encode(format!("{client_id}:{client_secret}"));
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("authorization", format!("Basic {credentials}"))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+// ─── DB helpers ───────────────────────────────────────────────────────────────
+
+/// Creates a role in the project bundling the given permission and assigns it to the account.
+async fn assign_role_with_permission(
+    pool: &PgPool,
+    project_id: uuid::Uuid,
+    account_id: uuid::Uuid,
+    permission_id: uuid::Uuid,
+) {
+    let role_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO project_roles (id, project_id, name) VALUES ($1, $2, 'clerk')")
+        .bind(role_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    sqlx::query("INSERT INTO project_role_permissions (role_id, permission_id) VALUES ($1, $2)")
+        .bind(role_id)
+        .bind(permission_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    sqlx::query("INSERT INTO account_roles (account_id, role_id) VALUES ($1, $2)")
+        .bind(account_id)
+        .bind(role_id)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+// ─── POST /authz/check ────────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn check_allows_direct_grant_and_denies_missing_one(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "alice@example.com").await;
+    let read_id = insert_permission(&pool, application_id, "orders:read").await;
+    insert_permission(&pool, application_id, "orders:write").await;
+    sqlx::query("INSERT INTO account_scopes (account_id, permission_id) VALUES ($1, $2)")
+        .bind(account_id)
+        .bind(read_id)
+        .execute(&pool)
+        .await?;
+
+    let allowed = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/check",
+            json!({ "account_id": account_id.to_string(), "permission": "orders:read" }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(allowed.status(), StatusCode::OK);
+    let body = json_body(allowed).await;
+    assert_eq!(body["allowed"], true);
+    assert_eq!(body["reason"], "direct_grant");
+    assert_eq!(body["cache_ttl"], study_auth::authz::DECISION_CACHE_TTL_SECS);
+
+    let denied = test_app(pool)
+        .oneshot(client_json_request(
+            "/authz/check",
+            json!({ "account_id": account_id.to_string(), "permission": "orders:write" }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(denied.status(), StatusCode::OK);
+    let body = json_body(denied).await;
+    assert_eq!(body["allowed"], false);
+    assert_eq!(body["reason"], "not_granted");
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn check_rejects_invalid_client_secret(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "alice@example.com").await;
+
+    let response = test_app(pool)
+        .oneshot(client_json_request(
+            "/authz/check",
+            json!({ "account_id": account_id.to_string(), "permission": "orders:read" }),
+            client_id,
+            "wrong-secret",
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    Ok(())
+}
+
+// ─── POST /authz/check/batch ──────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn batch_check_returns_decisions_in_request_order(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    let account_id = insert_user_account(&pool, project_id, "alice@example.com").await;
+    let write_id = insert_permission(&pool, application_id, "orders:write").await;
+    assign_role_with_permission(&pool, project_id, account_id, write_id).await;
+
+    // Accounts of another project are invisible to this application.
+    let other_project_id = insert_org_with_project(&pool, "Globex").await;
+    let stranger_id = insert_user_account(&pool, other_project_id, "mallory@example.com").await;
+
+    let response = test_app(pool)
+        .oneshot(client_json_request(
+            "/authz/check/batch",
+            json!({ "checks": [
+                { "account_id": account_id.to_string(), "permission": "orders:write" },
+                { "account_id": account_id.to_string(), "permission": "orders:delete" },
+                { "account_id": stranger_id.to_string(), "permission": "orders:write" },
+            ]}),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["allowed"], true);
+    assert_eq!(results[0]["reason"], "role_grant");
+    assert_eq!(results[1]["reason"], "unknown_permission");
+    assert!(results[1].get("cache_ttl").is_none());
+    assert_eq!(results[2]["reason"], "account_not_found");
+    Ok(())
+}
+
+// ─── Relationship tuples ──────────────────────────────────────────────────────
+
+/// Configures `team`, `folder` and `doc` namespaces where doc viewers include editors and
+/// viewers of the parent folder, and editors include owners.
+async fn insert_document_namespaces(pool: &PgPool, project_id: uuid::Uuid) {
+    let namespaces = [
+        ("team", json!({ "relations": { "member": {} } })),
+        ("folder", json!({ "relations": { "viewer": {} } })),
+        (
+            "doc",
+            json!({ "relations": {
+                "parent": {},
+                "owner": {},
+                "editor": { "rewrite": { "union": ["this", { "computed_userset": { "relation": "owner" } }] } },
+                "viewer": { "rewrite": { "union": [
+                    "this",
+                    { "computed_userset": { "relation": "editor" } },
+                    { "tuple_to_userset": { "tupleset": "parent", "computed_userset": "viewer" } },
+                ] } },
+            } }),
+        ),
+    ];
+
+    for (name, config) in namespaces {
+        sqlx::query("INSERT INTO relation_namespaces (project_id, name, config) VALUES ($1, $2, $3)")
+            .bind(project_id)
+            .bind(name)
+            .bind(config)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+async fn check_relation(
+    pool: &PgPool,
+    client_id: uuid::Uuid,
+    object: &str,
+    relation: &str,
+    subject: &str,
+    consistency_token: &str,
+) -> bool {
+    let response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/check",
+            json!({
+                "object": object,
+                "relation": relation,
+                "subject": subject,
+                "consistency_token": consistency_token,
+            }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response).await["allowed"].as_bool().unwrap()
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn relation_check_follows_usersets_and_rewrites(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    insert_document_namespaces(&pool, project_id).await;
+
+    let write_response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/tuples",
+            json!({ "writes": [
+                { "object": "team:a", "relation": "member", "subject": "user:bob" },
+                { "object": "doc:42", "relation": "editor", "subject": "team:a#member" },
+                { "object": "doc:42", "relation": "parent", "subject": "folder:f" },
+                { "object": "folder:f", "relation": "viewer", "subject": "user:carol" },
+                { "object": "doc:7", "relation": "owner", "subject": "user:alice" },
+            ]}),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(write_response.status(), StatusCode::OK);
+    let token = json_body(write_response).await["consistency_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    assert!(check_relation(&pool, client_id, "doc:42", "editor", "user:bob", &token).await);
+    assert!(check_relation(&pool, client_id, "doc:42", "viewer", "user:bob", &token).await);
+    assert!(check_relation(&pool, client_id, "doc:42", "viewer", "user:carol", &token).await);
+    assert!(!check_relation(&pool, client_id, "doc:42", "editor", "user:carol", &token).await);
+    assert!(check_relation(&pool, client_id, "doc:7", "viewer", "user:alice", &token).await);
+
+    let list_response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/list-objects",
+            json!({ "namespace": "doc", "relation": "viewer", "subject": "user:bob" }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(list_response.status(), StatusCode::OK);
+    assert_eq!(json_body(list_response).await["objects"], json!(["42"]));
+
+    let delete_response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/tuples",
+            json!({ "deletes": [{ "object": "team:a", "relation": "member", "subject": "user:bob" }] }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(delete_response.status(), StatusCode::OK);
+    let token = json_body(delete_response).await["consistency_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    assert!(!check_relation(&pool, client_id, "doc:42", "viewer", "user:bob", &token).await);
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn relation_expand_returns_rewrite_tree(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    insert_document_namespaces(&pool, project_id).await;
+
+    let write_response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/tuples",
+            json!({ "writes": [
+                { "object": "doc:42", "relation": "editor", "subject": "team:a#member" },
+                { "object": "doc:42", "relation": "editor", "subject": "user:dave" },
+            ]}),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(write_response.status(), StatusCode::OK);
+
+    let response = test_app(pool)
+        .oneshot(client_json_request(
+            "/authz/relations/expand",
+            json!({ "object": "doc:42", "relation": "editor" }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response).await;
+    assert_eq!(
+        body["tree"],
+        json!({ "type": "union", "children": [
+            { "type": "leaf", "subjects": ["team:a#member", "user:dave"] },
+            { "type": "leaf", "subjects": ["doc:42#owner"] },
+        ]})
+    );
+    Ok(())
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn relation_write_rejects_undeclared_relation(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    let (_application_id, client_id) = insert_application_with_client_id(&pool, project_id).await;
+    insert_document_namespaces(&pool, project_id).await;
+
+    let response = test_app(pool.clone())
+        .oneshot(client_json_request(
+            "/authz/relations/tuples",
+            json!({ "writes": [{ "object": "doc:42", "relation": "commenter", "subject": "user:bob" }] }),
+            client_id,
+            CLIENT_SECRET,
+        ))
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = json_body(response).await;
+    assert_eq!(body["errors"]["relation"][0], "unknown relation 'doc#commenter'");
+
+    let revision: i64 = sqlx::query_scalar("SELECT authz_revision FROM projects WHERE id = $1")
+        .bind(project_id)
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(revision, 0);
+    Ok(())
+}
@@ -0,0 +1,251 @@
+#![allow(dead_code)]
+
+//! Fixtures shared by the integration test crates.
+
+use axum::body::Body;
+use axum::http::Request;
+use http_body_util::BodyExt;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use std::sync::Once;
+
+/// The secret of every application created by `insert_application`.
+pub const CLIENT_SECRET: &str = "existing-secret";
+
+/// The password of every admin and user account created here.
+pub const PASSWORD: &str = "password-123";
+
+pub fn init_test_env() {
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| unsafe {
+        std::env::set_var("ADMIN_ACCESS_TOKEN_DURATION_IN_MINUTES", "60");
+        std::env::set_var("POSTGRES_MAX_CONNECTIONS", "5");
+        std::env::set_var("POSTGRES_ACQUIRE_TIMEOUT_IN_SECS", "5");
+        std::env::set_var("RATE_LIMITER_GC_MAX_MEMORY_IN_MB", "64");
+        std::env::set_var("USER_ACCESS_TOKEN_DURATION_IN_MINUTES", "60");
+        std::env::set_var("ADMIN_JWT_SECRET", "test-admin-secret");
+        std::env::set_var("USER_JWT_SECRET", "test-user-secret");
+        std::env::set_var("JWT_ISSUER", "http://localhost:3000");
+        std::env::set_var("AUTH_EVENTS_RETENTION_IN_DAYS", "90");
+    });
+}
+
+pub fn test_app(pool: PgPool) -> axum::Router {
+    let state = study_auth::router::AppState::new(pool);
+    study_auth::router::routes(state)
+}
+
+pub async fn json_body(response: axum::response::Response) -> Value {
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body_bytes).unwrap_or_else(|_| json!({}))
+}
+
+pub async fn text_body(response: axum::response::Response) -> String {
+    let body_bytes = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body_bytes.to_vec()).unwrap()
+}
+
+pub fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+pub fn auth_json_request(method: &str, uri: &str, body: Value, token: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {token}"))
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+pub fn auth_request(method: &str, uri: &str, token: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap()
+}
+
+pub fn form_request(uri: &str, form: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(form.to_string()))
+        .unwrap()
+}
+
+pub fn admin_token(admin_id: uuid::Uuid) -> String {
+    init_test_env();
+    study_auth::jwt::generate_admin_token(&admin_id.to_string())
+        .unwrap_or_else(|_| panic!("failed to generate admin token for tests"))
+}
+
+// ─── DB helpers ───────────────────────────────────────────────────────────────
+
+pub async fn insert_admin_user(pool: &PgPool, username: &str) -> uuid::Uuid {
+    let admin_id = study_auth::id::new_uuid();
+    let password_hash = study_auth::crypto::hash_password(PASSWORD).unwrap();
+    sqlx::query("INSERT INTO admin_users (id, username, password_hash) VALUES ($1, $2, $3)")
+        .bind(admin_id)
+        .bind(username)
+        .bind(password_hash)
+        .execute(pool)
+        .await
+        .unwrap();
+    admin_id
+}
+
+/// Creates an admin user and returns (admin_id, jwt_token).
+pub async fn create_admin(pool: &PgPool, username: &str) -> (uuid::Uuid, String) {
+    let admin_id = insert_admin_user(pool, username).await;
+    let token = admin_token(admin_id);
+    (admin_id, token)
+}
+
+pub async fn insert_organization(pool: &PgPool, name: &str) -> uuid::Uuid {
+    let org_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO organizations (id, name) VALUES ($1, $2)")
+        .bind(org_id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+    org_id
+}
+
+pub async fn insert_project(pool: &PgPool, org_id: uuid::Uuid, name: &str) -> uuid::Uuid {
+    let project_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO projects (id, org_id, name, shared_identity_context) VALUES ($1, $2, $3, $4)")
+        .bind(project_id)
+        .bind(org_id)
+        .bind(name)
+        .bind(false)
+        .execute(pool)
+        .await
+        .unwrap();
+    project_id
+}
+
+/// Creates an org with the given name and a project inside it; returns the project id.
+pub async fn insert_org_with_project(pool: &PgPool, org_name: &str) -> uuid::Uuid {
+    let org_id = insert_organization(pool, org_name).await;
+    insert_project(pool, org_id, "Project X").await
+}
+
+/// Creates an application whose secret is `CLIENT_SECRET`; returns the application id.
+pub async fn insert_application(pool: &PgPool, project_id: uuid::Uuid) -> uuid::Uuid {
+    let application_id = study_auth::id::new_uuid();
+    let client_id = study_auth::id::new_uuid();
+    sqlx::query(
+        "INSERT INTO applications (id, project_id, name, client_id, redirect_uris) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(application_id)
+    .bind(project_id)
+    .bind("Test Application")
+    .bind(client_id)
+    .bind(vec!["https://example.com/callback"])
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let secret_hash = study_auth::crypto::hash_password(CLIENT_SECRET).unwrap();
+    sqlx::query("INSERT INTO application_secrets (id, application_id, secret_hash) VALUES ($1, $2, $3)")
+        .bind(study_auth::id::new_uuid())
+        .bind(application_id)
+        .bind(secret_hash)
+        .execute(pool)
+        .await
+        .unwrap();
+    application_id
+}
+
+/// Like `insert_application`, for callers that authenticate as the client; returns (application_id, client_id).
+pub async fn insert_application_with_client_id(pool: &PgPool, project_id: uuid::Uuid) -> (uuid::Uuid, uuid::Uuid) {
+    let application_id = insert_application(pool, project_id).await;
+    (application_id, application_client_id(pool, application_id).await)
+}
+
+pub async fn application_client_id(pool: &PgPool, application_id: uuid::Uuid) -> uuid::Uuid {
+    sqlx::query_scalar("SELECT client_id FROM applications WHERE id = $1")
+        .bind(application_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+pub async fn insert_org_membership(pool: &PgPool, admin_id: uuid::Uuid, org_id: uuid::Uuid, role: &str) {
+    let id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO admin_org_memberships (id, admin_user_id, org_id, role) VALUES ($1, $2, $3, $4)")
+        .bind(id)
+        .bind(admin_id)
+        .bind(org_id)
+        .bind(role)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+pub async fn insert_project_membership(pool: &PgPool, admin_id: uuid::Uuid, project_id: uuid::Uuid, role: &str) {
+    let id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO admin_project_memberships (id, admin_user_id, project_id, role) VALUES ($1, $2, $3, $4)")
+        .bind(id)
+        .bind(admin_id)
+        .bind(project_id)
+        .bind(role)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Creates an identity with an email login method and an account in the project; returns the account id.
+pub async fn insert_user_account(pool: &PgPool, project_id: uuid::Uuid, email: &str) -> uuid::Uuid {
+    let identity_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO identities (id) VALUES ($1)")
+        .bind(identity_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let password_hash = study_auth::crypto::hash_password(PASSWORD).unwrap();
+    sqlx::query(
+        "INSERT INTO login_methods (id, identity_id, method_type, identifier, password_hash) VALUES ($1, $2, 'email', $3, $4)",
+    )
+    .bind(study_auth::id::new_uuid())
+    .bind(identity_id)
+    .bind(email)
+    .bind(password_hash)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let account_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO user_accounts (id, identity_id, project_id) VALUES ($1, $2, $3)")
+        .bind(account_id)
+        .bind(identity_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    account_id
+}
+
+pub async fn insert_permission(pool: &PgPool, application_id: uuid::Uuid, name: &str) -> uuid::Uuid {
+    let permission_id = study_auth::id::new_uuid();
+    sqlx::query("INSERT INTO permissions (id, app_id, name, description) VALUES ($1, $2, $3, $3)")
+        .bind(permission_id)
+        .bind(application_id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+    permission_id
+}
@@ -0,0 +1,266 @@
+mod common;
+
+use common::*;
+use sqlx::PgPool;
+use study_auth::jobs::{self, Job, RunOutcome};
+
+// ─── DB helpers ───────────────────────────────────────────────────────────────
+
+/// Creates an org owned by a fresh admin, who issues the invites below; returns the org ID.
+async fn insert_org_with_inviter(pool: &PgPool) -> uuid::Uuid {
+    let admin_id = insert_admin_user(pool, "inviter").await;
+    let org_id = insert_organization(pool, "Acme").await;
+    insert_org_membership(pool, admin_id, org_id, "owner").await;
+    org_id
+}
+
+/// Inserts a pending membership invite expiring `expires_in_hours` from now (negative for the past).
+async fn insert_invite(pool: &PgPool, org_id: uuid::Uuid, invitee: &str, expires_in_hours: i32) -> uuid::Uuid {
+    let invite_id = study_auth::id::new_uuid();
+    sqlx::query(
+        r#"
+            INSERT INTO admin_invites
+                (id, invited_by_admin_user_id, org_id, invitee_username, role, status, expires_at)
+            SELECT $1, admin_user_id, $2, $3, 'admin', 'pending', NOW() + make_interval(hours => $4)
+            FROM admin_org_memberships WHERE org_id = $2
+        "#,
+    )
+    .bind(invite_id)
+    .bind(org_id)
+    .bind(invitee)
+    .bind(expires_in_hours)
+    .execute(pool)
+    .await
+    .unwrap();
+    invite_id
+}
+
+async fn invite_status(pool: &PgPool, invite_id: uuid::Uuid) -> String {
+    sqlx::query_scalar("SELECT status FROM admin_invites WHERE id = $1")
+        .bind(invite_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn insert_auth_event(pool: &PgPool, age_in_days: i32) {
+    sqlx::query(
+        r#"
+            INSERT INTO auth_events (id, event_type, success, route, occurred_at)
+            VALUES ($1, 'login', true, '/auth/login', NOW() - make_interval(days => $2))
+        "#,
+    )
+    .bind(study_auth::id::new_uuid())
+    .bind(age_in_days)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Inserts a device authorization for a fresh application, expiring `expires_in_secs` from now.
+/// Org names are unique, so each request gets an org named after its user code.
+async fn insert_device_authorization(pool: &PgPool, user_code: &str, expires_in_secs: i32) {
+    let project_id = insert_org_with_project(pool, user_code).await;
+    let application_id = insert_application(pool, project_id).await;
+    sqlx::query(
+        r#"
+            INSERT INTO device_authorizations (id, application_id, device_code_hash, user_code, expires_at)
+            VALUES ($1, $2, 'x', $3, NOW() + make_interval(secs => $4))
+        "#,
+    )
+    .bind(study_auth::id::new_uuid())
+    .bind(application_id)
+    .bind(user_code)
+    .bind(expires_in_secs as f64)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// `AppError` has no `Debug`, so `unwrap` is not available on the result.
+async fn run(pool: &PgPool, job: Job) -> RunOutcome {
+    match jobs::run_once(pool, job).await {
+        Ok(outcome) => outcome,
+        Err(_) => panic!("job {} failed", job.name()),
+    }
+}
+
+// ─── Invite expiry ────────────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn expire_invites_marks_only_past_due_invites_and_records_the_run(pool: PgPool) {
+    init_test_env();
+    let org_id = insert_org_with_inviter(&pool).await;
+    let past_due = insert_invite(&pool, org_id, "late", -1).await;
+    let current = insert_invite(&pool, org_id, "early", 24).await;
+
+    let outcome = run(&pool, Job::ExpireInvites).await;
+
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 1 });
+    assert_eq!(invite_status(&pool, past_due).await, "expired");
+    assert_eq!(invite_status(&pool, current).await, "pending");
+
+    let (status, affected_rows): (String, i64) =
+        sqlx::query_as("SELECT status, affected_rows FROM job_runs WHERE job_name = 'expire_invites'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "succeeded");
+    assert_eq!(affected_rows, 1);
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn job_is_skipped_within_its_interval(pool: PgPool) {
+    init_test_env();
+
+    let first = run(&pool, Job::ExpireInvites).await;
+    let second = run(&pool, Job::ExpireInvites).await;
+
+    assert_eq!(first, RunOutcome::Completed { affected_rows: 0 });
+    assert_eq!(second, RunOutcome::Skipped);
+
+    let runs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM job_runs")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(runs, 1);
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn job_is_skipped_while_another_replica_holds_the_lock(pool: PgPool) {
+    init_test_env();
+    let org_id = insert_org_with_inviter(&pool).await;
+    let past_due = insert_invite(&pool, org_id, "late", -1).await;
+
+    let mut other_replica = pool.begin().await.unwrap();
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock(hashtext('job:' || $1))")
+        .bind(Job::ExpireInvites.name())
+        .fetch_one(&mut *other_replica)
+        .await
+        .unwrap();
+    assert!(locked);
+
+    let outcome = run(&pool, Job::ExpireInvites).await;
+    assert_eq!(outcome, RunOutcome::Skipped);
+    assert_eq!(invite_status(&pool, past_due).await, "pending");
+
+    other_replica.rollback().await.unwrap();
+    let outcome = run(&pool, Job::ExpireInvites).await;
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 1 });
+}
+
+// ─── Auth event retention ─────────────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn auth_events_retention_deletes_events_past_the_retention_period(pool: PgPool) {
+    init_test_env();
+    insert_auth_event(&pool, 91).await;
+    insert_auth_event(&pool, 89).await;
+
+    let outcome = run(&pool, Job::AuthEventsRetention).await;
+
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 1 });
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM auth_events")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, 1);
+}
+
+// ─── Device authorization purge ───────────────────────────────────────────────
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn purge_device_authorizations_deletes_expired_requests(pool: PgPool) {
+    init_test_env();
+    insert_device_authorization(&pool, "BCDF-GHJK", -1).await;
+    insert_device_authorization(&pool, "LMNP-QRST", 600).await;
+
+    let outcome = run(&pool, Job::PurgeDeviceAuthorizations).await;
+
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 1 });
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT user_code FROM device_authorizations")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, ["LMNP-QRST"]);
+}
+
+// ─── Relation tuple purge ─────────────────────────────────────────────────────
+
+/// Inserts a `doc:<object_id>#viewer@user:alice` tuple, deleted `deleted_minutes_ago` minutes ago when set.
+async fn insert_relation_tuple(
+    pool: &PgPool,
+    project_id: uuid::Uuid,
+    object_id: &str,
+    deleted_minutes_ago: Option<i32>,
+) {
+    sqlx::query(
+        r#"
+            INSERT INTO relation_tuples
+                (id, project_id, namespace, object_id, relation, subject_namespace, subject_id, created_revision,
+                 deleted_revision, deleted_at)
+            VALUES ($1, $2, 'doc', $3, 'viewer', 'user', 'alice', 1,
+                    CASE WHEN $4::int IS NULL THEN NULL ELSE 2 END, NOW() - make_interval(mins => $4))
+        "#,
+    )
+    .bind(study_auth::id::new_uuid())
+    .bind(project_id)
+    .bind(object_id)
+    .bind(deleted_minutes_ago)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn purge_relation_tuples_deletes_tuples_no_snapshot_can_read(pool: PgPool) {
+    init_test_env();
+    let project_id = insert_org_with_project(&pool, "Acme").await;
+    insert_relation_tuple(&pool, project_id, "live", None).await;
+    insert_relation_tuple(&pool, project_id, "just-deleted", Some(1)).await;
+    insert_relation_tuple(&pool, project_id, "long-deleted", Some(60)).await;
+
+    let outcome = run(&pool, Job::PurgeRelationTuples).await;
+
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 1 });
+    let remaining: Vec<String> = sqlx::query_scalar("SELECT object_id FROM relation_tuples ORDER BY object_id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, ["just-deleted", "live"]);
+}
+
+async fn insert_job_run(pool: &PgPool, job: Job, status: &str, age_in_days: i32) -> uuid::Uuid {
+    let run_id = study_auth::id::new_uuid();
+    sqlx::query(
+        "INSERT INTO job_runs (id, job_name, status, started_at) VALUES ($1, $2, $3, NOW() - make_interval(days => $4))",
+    )
+    .bind(run_id)
+    .bind(job.name())
+    .bind(status)
+    .bind(age_in_days)
+    .execute(pool)
+    .await
+    .unwrap();
+    run_id
+}
+
+#[sqlx::test(migrations = "infra/migrations")]
+async fn job_runs_retention_keeps_recent_runs_and_each_jobs_latest_success(pool: PgPool) {
+    init_test_env();
+    let latest_success = insert_job_run(&pool, Job::ExpireInvites, "succeeded", 40).await;
+    insert_job_run(&pool, Job::ExpireInvites, "succeeded", 50).await;
+    insert_job_run(&pool, Job::ExpireInvites, "failed", 35).await;
+    let recent_failure = insert_job_run(&pool, Job::AuthEventsRetention, "failed", 1).await;
+
+    let outcome = run(&pool, Job::JobRunsRetention).await;
+
+    assert_eq!(outcome, RunOutcome::Completed { affected_rows: 2 });
+    let remaining: Vec<uuid::Uuid> =
+        sqlx::query_scalar("SELECT id FROM job_runs WHERE job_name <> $1 ORDER BY started_at")
+            .bind(Job::JobRunsRetention.name())
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, [latest_success, recent_failure]);
+}